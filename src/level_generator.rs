@@ -0,0 +1,98 @@
+//! SkipList 节点层级的生成器。
+
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+/// 层级生成器，为新插入的节点决定它的层级。
+///
+/// 生成的层级必须落在 `[0, total)` 区间内。
+pub trait LevelGenerator {
+    /// 可生成的层级总数。
+    fn total(&self) -> usize;
+    /// 生成一个随机层级。
+    fn random(&mut self) -> usize;
+}
+
+/// 几何分布层级生成器。
+///
+/// 节点位于第 `n` 层的概率为 `p^n`，因此每升高一层，节点数量大约变为原来的 `p` 倍。
+#[derive(Clone)]
+pub struct GeometricalLevelGenerator {
+    total: usize,
+    p: f64,
+    state: u64,
+}
+
+impl GeometricalLevelGenerator {
+    /// 创建一个新的几何层级生成器，共有 `total` 层，每一层的晋升概率为 `p`。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `total` 为 0，或 `p` 不在 `(0, 1)` 区间内，则会恐慌。
+    pub fn new(total: usize, p: f64) -> Self {
+        if total == 0 {
+            panic!("total must be non-zero.");
+        }
+        // 写成取反的形式，使 NaN 也被拒绝
+        if !(p > 0.0 && p < 1.0) {
+            panic!("p must be in (0, 1).");
+        }
+        // 种子来自标准库的随机哈希键，xorshift 的状态不能为 0
+        let state = RandomState::new().build_hasher().finish() | 1;
+        GeometricalLevelGenerator { total, p, state }
+    }
+
+    /// 修改可生成的层级总数，之后生成的层级落在 `[0, total)` 区间内。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `total` 为 0，则会恐慌。
+    pub fn set_total(&mut self, total: usize) {
+        if total == 0 {
+            panic!("total must be non-zero.");
+        }
+        self.total = total;
+    }
+
+    /// 修改每一层的晋升概率，只影响之后生成的层级。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `p` 不在 `(0, 1)` 区间内，则会恐慌。
+    pub fn set_p(&mut self, p: f64) {
+        // 写成取反的形式，使 NaN 也被拒绝
+        if !(p > 0.0 && p < 1.0) {
+            panic!("p must be in (0, 1).");
+        }
+        self.p = p;
+    }
+
+    /// 生成 `[0, 1)` 区间内均匀分布的随机数。
+    fn next_f64(&mut self) -> f64 {
+        // xorshift64*
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let x = self.state.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+impl LevelGenerator for GeometricalLevelGenerator {
+    fn total(&self) -> usize {
+        self.total
+    }
+
+    fn random(&mut self) -> usize {
+        let mut h = 0;
+        let mut x = self.p;
+        let f = 1.0 - self.next_f64();
+        while x > f && h + 1 < self.total {
+            h += 1;
+            x *= self.p;
+        }
+        h
+    }
+}
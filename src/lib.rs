@@ -0,0 +1,7 @@
+//! 跳表的简单实现。
+
+pub mod level_generator;
+pub mod skiplist;
+mod skipnode;
+
+pub use crate::skiplist::SkipList;
@@ -1,7 +1,3 @@
-pub mod skiplist;
-pub mod level_generator;
-mod skipnode;
-
 fn main() {
     println!("Hello, world!");
 }
@@ -1,15 +1,20 @@
 //! 一个SkipList实现，它有着比标准链表更快的随机访问。
 
 use std::{
-    cmp, cmp::Ordering, default, fmt, hash, hash::Hash, iter, ops, ops::Bound, ptr::NonNull,
+    cmp,
+    collections::{BinaryHeap, TryReserveError},
+    default, fmt, iter,
+    marker::PhantomData,
+    mem, ops,
+    ptr::{self, NonNull},
 };
 
 use crate::{
     level_generator::{GeometricalLevelGenerator, LevelGenerator},
-    skipnode::SkipNode,
+    skipnode::{RetainPass, SkipNode},
 };
 
-pub use crate::skipnode::{IntoIter, Iter, IterMut};
+pub use crate::skipnode::{IntoIter, Iter, IterMut, NodeChain, NodePtr};
 
 /// SkipList提供了一种存储元素的方式，并提供了访问、插入和删除节点方法。
 /// 与标准链表不同，SkipList可以通过较少的代价找到一个特定的索引。
@@ -17,6 +22,7 @@ pub struct SkipList<T> {
     head: Box<SkipNode<T>>,
     len: usize,
     level_generator: GeometricalLevelGenerator, //几何层级生成器
+    auto_shrink: bool,
 }
 /// SkipList的固有方法
 impl<T> SkipList<T> {
@@ -26,7 +32,7 @@ impl<T> SkipList<T> {
     /// ```
     /// use skiplist::SkipList;
     ///
-    /// let mut skiplist: SkipList<i64> = Skip::new();
+    /// let mut skiplist: SkipList<i64> = SkipList::new();
     /// ```
     #[inline]
     pub fn new() -> Self {
@@ -35,47 +41,282 @@ impl<T> SkipList<T> {
             head: Box::new(SkipNode::head(lg.total())),
             len: 0,
             level_generator: lg,
+            auto_shrink: false,
         }
     }
 
     /// 构造一个新的空的 skiplist，其中包含预期容量的最佳级别数。
-    /// 具体来说，它使用 “楼层（log2（容量））” 级别数，确保只有几个节点占据最高级别。
+    /// 具体来说，它使用 “上取整（log2（容量））” 级别数，使 [`capacity`](SkipList::capacity) 不小于 `capacity`，
+    /// 同时只有几个节点占据最高级别。容量小于 2 时仍然至少使用 1 个级别。
     ///
-    /// ```
     /// # Examples
+    ///
+    /// ```
     /// use skiplist::SkipList;
+    ///
     /// let mut skiplist = SkipList::with_capacity(100);
     /// skiplist.extend(0..100);
+    ///
+    /// for capacity in 0..4 {
+    ///     let mut skiplist = SkipList::with_capacity(capacity);
+    ///     skiplist.extend(0..capacity);
+    ///     assert_eq!(skiplist.len(), capacity);
+    ///     assert!(skiplist.iter().copied().eq(0..capacity));
+    /// }
+    ///
+    /// for capacity in [5, 100, 1000, 1024, 1025, 10_000] {
+    ///     assert!(SkipList::<i32>::with_capacity(capacity).capacity() >= capacity);
+    /// }
+    /// assert_eq!(SkipList::<i32>::with_capacity(1000).capacity(), 1024);
     /// ```
     #[inline]
     pub fn with_capacity(capacity: usize) -> Self {
-        let levels = cmp::max(1, (capacity as f64).log2().floor() as usize);
-        let lg = GeometricalLevelGenerator::new(levels, 1.0 / 2.0);
+        SkipList::with_capacity_and_probability(capacity, 1.0 / 2.0)
+    }
+
+    /// 与 [`with_capacity`](SkipList::with_capacity) 相同，根据预期容量选择级别数，
+    /// 但每一层的晋升概率使用 `p`。
+    ///
+    /// 较小的 `p` 使高层节点更少，占用更少的链接，但查找时需要在每一层走更多步。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `p` 不在 `(0, 1)` 区间内，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity_and_probability(10_000, 0.25);
+    /// skiplist.extend(0..10_000);
+    ///
+    /// // 大约 3/4 的节点停留在第 0 层
+    /// let histogram = skiplist.level_histogram();
+    /// let higher: usize = histogram[1..].iter().sum();
+    /// assert!(histogram[0] > 2 * higher);
+    ///
+    /// // 容量为 0 时仍然至少有 1 层
+    /// assert_eq!(SkipList::<i32>::with_capacity_and_probability(0, 0.5).head_level(), 1);
+    ///
+    /// for p in [0.0, 1.0, -0.5, 1.5, f64::NAN] {
+    ///     let result =
+    ///         std::panic::catch_unwind(|| SkipList::<i32>::with_capacity_and_probability(16, p));
+    ///     let message = result.unwrap_err().downcast::<&str>().unwrap();
+    ///     assert_eq!(*message, "p must be in (0, 1).");
+    /// }
+    /// ```
+    pub fn with_capacity_and_probability(capacity: usize, p: f64) -> Self {
+        let lg = GeometricalLevelGenerator::new(levels_for(capacity), p);
         SkipList {
             head: Box::new(SkipNode::head(lg.total())),
             len: 0,
             level_generator: lg,
+            auto_shrink: false,
+        }
+    }
+
+    /// 构造一个包含 `n` 个 `value` 副本的 skiplist，节点按均衡布局排列。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist = SkipList::repeat(7, 5);
+    /// assert_eq!(skiplist.len(), 5);
+    /// assert!(skiplist.iter().all(|&x| x == 7));
+    ///
+    /// assert!(SkipList::repeat('a', 0).is_empty());
+    /// ```
+    pub fn repeat(value: T, n: usize) -> Self
+    where
+        T: Clone,
+    {
+        // `repeat_n` 的最后一个副本直接使用 `value` 本身，省去一次克隆
+        SkipList::from_balanced(n, iter::repeat_n(value, n))
+    }
+
+    /// 按顺序克隆一个排好序的切片，以均衡布局构造 skiplist，耗时 O(n)。
+    ///
+    /// 构造过程不检查顺序；切片排好序时，之后可以直接使用
+    /// [`binary_search_by_key`](SkipList::binary_search_by_key) 等查找方法。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let values: Vec<_> = (0..1000).map(|x| x * 2).collect();
+    /// let skiplist = SkipList::from_sorted_slice(&values);
+    /// assert!(skiplist.eq_slice(&values));
+    /// assert_eq!(skiplist.binary_search_by_key(&500, |&x| x), Ok(250));
+    /// assert_eq!(skiplist.binary_search_by_key(&501, |&x| x), Err(251));
+    /// ```
+    pub fn from_sorted_slice(s: &[T]) -> Self
+    where
+        T: Clone,
+    {
+        SkipList::from_balanced(s.len(), s.iter().cloned())
+    }
+
+    /// 把若干个各自排好序的切片多路归并为一个排好序的 skiplist，节点按均衡布局排列。
+    ///
+    /// 相等的元素按切片的先后顺序排列。共 `n` 个元素、`k` 个切片时耗时 O(n log k)。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist = SkipList::from_merged(&[&[1, 4], &[2, 5], &[3, 6]]);
+    /// assert!(skiplist.iter().eq([1, 2, 3, 4, 5, 6].iter()));
+    ///
+    /// let skiplist = SkipList::from_merged(&[&[1, 1, 9], &[], &[0, 1]]);
+    /// assert!(skiplist.iter().eq([0, 1, 1, 1, 9].iter()));
+    /// ```
+    pub fn from_merged(slices: &[&[T]]) -> Self
+    where
+        T: Ord + Clone,
+    {
+        let len = slices.iter().map(|slice| slice.len()).sum();
+        // 堆中保存每个切片当前的最小元素，以及它所在的切片和位置
+        let mut heap: BinaryHeap<_> = slices
+            .iter()
+            .enumerate()
+            .filter_map(|(i, slice)| slice.first().map(|first| cmp::Reverse((first, i, 0))))
+            .collect();
+        let merged = iter::from_fn(|| {
+            let cmp::Reverse((value, i, j)) = heap.pop()?;
+            if let Some(next) = slices[i].get(j + 1) {
+                heap.push(cmp::Reverse((next, i, j + 1)));
+            }
+            Some(value.clone())
+        });
+        SkipList::from_balanced(len, merged)
+    }
+
+    /// 从一个产生 `Result` 的迭代器构造 skiplist，遇到第一个 `Err` 时立即返回它。
+    ///
+    /// 出错时已经插入的元素会随部分构造的 skiplist 一起被释放。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::rc::Rc;
+    /// use skiplist::SkipList;
+    ///
+    /// let ok = SkipList::try_from_iter([Ok::<_, ()>(1), Ok(2)]).unwrap();
+    /// assert!(ok.iter().eq([1, 2].iter()));
+    ///
+    /// let shared = Rc::new(0);
+    /// let items = vec![Ok(shared.clone()), Ok(shared.clone()), Err("third"), Ok(shared.clone())];
+    /// assert_eq!(SkipList::try_from_iter(items).err(), Some("third"));
+    /// // 已经插入的元素都被释放了
+    /// assert_eq!(Rc::strong_count(&shared), 1);
+    /// ```
+    pub fn try_from_iter<E, I>(iter: I) -> Result<SkipList<T>, E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+    {
+        let mut skiplist = SkipList::new();
+        for item in iter {
+            skiplist.push_back(item?);
+        }
+        Ok(skiplist)
+    }
+
+    /// 把 skiplist 拆分为节点链、长度和层级生成器，可以通过 [`from_parts`](SkipList::from_parts) 重新组装。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// let (chain, len, level_generator) = skiplist.into_parts();
+    /// assert_eq!(len, 10);
+    ///
+    /// let skiplist = unsafe { SkipList::from_parts(chain, len, level_generator) };
+    /// assert!(skiplist.iter().copied().eq(0..10));
+    /// ```
+    pub fn into_parts(self) -> (NodeChain<T>, usize, GeometricalLevelGenerator) {
+        (NodeChain(self.head), self.len, self.level_generator)
+    }
+
+    /// 由 [`into_parts`](SkipList::into_parts) 拆分出的各部分重新组装 skiplist。
+    ///
+    /// # Safety
+    ///
+    /// 节点链的结构由 [`NodeChain`] 保证，调用方还必须保证：
+    ///
+    /// - `len` 恰好是 `chain` 中的元素个数；
+    /// - `level_generator` 生成的层级不超过 `chain` 的头节点的层级。
+    ///
+    /// 传入的 `len` 和 `level_generator` 来自拆分出 `chain` 的同一次 `into_parts` 时即可满足。
+    /// 重新组装的 skiplist 不会自动收缩，见 [`set_auto_shrink`](SkipList::set_auto_shrink)。
+    pub unsafe fn from_parts(
+        chain: NodeChain<T>,
+        len: usize,
+        level_generator: GeometricalLevelGenerator,
+    ) -> Self {
+        SkipList {
+            head: chain.0,
+            len,
+            level_generator,
+            auto_shrink: false,
         }
     }
 
     /// 清空 skiplist, 移除所有值.
     ///
+    /// 头节点的层数保持不变，因此容量保留下来，便于重新填充。
+    /// 需要同时释放层数时使用 [`clear_and_shrink`](SkipList::clear_and_shrink)。
+    ///
     /// # Examples
     ///
     /// ```
     /// use skiplist::SkipList;
     ///
-    /// let mut skiplist = SkipList::new();
+    /// let mut skiplist = SkipList::with_capacity(1024);
     /// skiplist.extend(0..10);
     /// skiplist.clear();
     /// assert!(skiplist.is_empty());
+    /// assert_eq!(skiplist.capacity(), 1024);
+    ///
+    /// skiplist.extend(0..10);
+    /// assert!(skiplist.iter().copied().eq(0..10));
     /// ```
     #[inline]
     pub fn clear(&mut self) {
         self.len = 0;
-        *self.head = SkipNode::head(self.level_generator.total());
+        *self.head = SkipNode::head(self.head_level());
     }
 
+    /// 清空 skiplist，并把头节点和层级生成器重置为 1 层。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(1024);
+    /// skiplist.extend(0..10);
+    /// skiplist.clear_and_shrink();
+    /// assert!(skiplist.is_empty());
+    /// assert_eq!(skiplist.head_level(), 1);
+    /// assert_eq!(skiplist.capacity(), 2);
+    ///
+    /// // 之后插入的节点都只有 1 层
+    /// skiplist.extend(0..10);
+    /// assert_eq!(skiplist.max_node_level(), 1);
+    /// ```
+    #[inline]
+    pub fn clear_and_shrink(&mut self) {
+        self.len = 0;
+        *self.head = SkipNode::head(1);
+        self.level_generator.set_total(1);
+    }
 
     /// 获取 skiplist 元素个数
     ///
@@ -126,10 +367,14 @@ impl<T> SkipList<T> {
     /// skiplist.insert(5, 1);
     /// assert_eq!(skiplist.len(), 2);
     /// assert!(!skiplist.is_empty());
+    ///
+    /// let result = std::panic::catch_unwind(move || skiplist.insert(9, 12));
+    /// let message = result.unwrap_err().downcast::<String>().unwrap();
+    /// assert_eq!(*message, "index 12 out of bounds for skiplist of length 2");
     /// ```
     pub fn insert(&mut self, value: T, index:usize) {
         if index > self.len {
-            panic!("Index out of bounds");
+            index_out_of_bounds(index, self.len);
         }
         self.len += 1;
         let new_node = Box::new(SkipNode::new(value, self.level_generator.random()));
@@ -138,6 +383,299 @@ impl<T> SkipList<T> {
             .unwrap_or_else(|_| panic!("No insertion position is found!"));
     }
 
+    /// 与 [`insert`](SkipList::insert) 相同，并返回元素最终所在的索引。
+    ///
+    /// 对按位置插入的 skiplist，返回值总是 `index`；提供它是为了让调用方不必关心元素落在哪里。
+    ///
+    /// # Panics
+    ///
+    /// 如果插入索引大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let index = skiplist.insert_indexed(10, 2);
+    /// assert_eq!(index, 2);
+    /// assert_eq!(skiplist.iter().position(|&x| x == 10), Some(index));
+    /// ```
+    pub fn insert_indexed(&mut self, value: T, index: usize) -> usize {
+        self.insert(value, index);
+        index
+    }
+
+    /// 在 skiplist 的头部插入元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.push_front(1);
+    /// skiplist.push_front(2);
+    /// assert_eq!(skiplist.get(0), Some(&2));
+    /// ```
+    #[inline]
+    pub fn push_front(&mut self, value: T) {
+        self.insert(value, 0);
+    }
+
+    /// 在 skiplist 的尾部插入元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.push_back(1);
+    /// skiplist.push_back(2);
+    /// assert_eq!(skiplist.get(1), Some(&2));
+    /// ```
+    #[inline]
+    pub fn push_back(&mut self, value: T) {
+        let len = self.len;
+        self.insert(value, len);
+    }
+
+    /// [`push_front`](SkipList::push_front) 的别名。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (1..3).collect();
+    /// skiplist.prepend(0);
+    /// assert!(skiplist.iter().eq([0, 1, 2].iter()));
+    /// ```
+    #[inline]
+    pub fn prepend(&mut self, value: T) {
+        self.push_front(value);
+    }
+
+    /// [`push_back`](SkipList::push_back) 的别名。
+    ///
+    /// 与 `push_back` 走同一条插入路径。skiplist 不保存指向末尾的链接，
+    /// 因此每次追加都需要一次 O(log n) 的下降。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// for i in 0..100 {
+    ///     skiplist.append_one(i);
+    /// }
+    /// assert_eq!(skiplist.len(), 100);
+    /// assert!(skiplist.iter().copied().eq(0..100));
+    /// ```
+    #[inline]
+    pub fn append_one(&mut self, value: T) {
+        self.push_back(value);
+    }
+
+    /// 在 skiplist 的头部依次插入迭代器中的元素，保持迭代器原有的顺序。
+    ///
+    /// 新元素先组成一条独立的链，再一次性拼接到头节点之后。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.push_back('x');
+    /// skiplist.extend_front(['a', 'b']);
+    /// assert!(skiplist.iter().eq(['a', 'b', 'x'].iter()));
+    /// ```
+    pub fn extend_front<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let (mut front, added) = self.detached_chain(iter);
+        front.append(added, &mut self.head, self.len);
+        self.head = front;
+        self.len += added;
+    }
+
+    /// 把 `iter` 中的元素排序后合并进已经排好序的 skiplist，整体仍然有序。
+    ///
+    /// 只需一次合并遍历，每移动一个节点的代价只与层数有关，而不必为每个新元素从头查找插入位置。
+    /// 与已有元素相等的新元素排在已有元素之后。已有节点被直接复用。
+    /// 如果 skiplist 没有排序，结果只是两个序列的合并。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = [1, 3, 5, 7, 9].into_iter().collect();
+    /// skiplist.insert_all([8, 2, 10, 0, 5]);
+    /// assert!(skiplist.iter().eq([0, 1, 2, 3, 5, 5, 7, 8, 9, 10].iter()));
+    /// ```
+    pub fn insert_all<I>(&mut self, iter: I)
+    where
+        T: Ord,
+        I: IntoIterator<Item = T>,
+    {
+        let mut items: Vec<T> = iter.into_iter().collect();
+        items.sort();
+        let mut items = items.into_iter().peekable();
+        let levels = self.head.level + 1;
+        let mut old = mem::replace(&mut self.head, Box::new(SkipNode::head(levels)));
+        let mut old_len = mem::replace(&mut self.len, 0);
+        let mut tails = self.head.tails(0);
+        while let Some(next) = items.peek() {
+            let take_old = old
+                .next_ref()
+                .and_then(|node| node.item.as_ref())
+                .is_some_and(|value| value <= next);
+            let node = if take_old {
+                old_len -= 1;
+                old.remove_at(0).unwrap()
+            } else {
+                let value = items.next().unwrap();
+                Box::new(SkipNode::new(value, self.level_generator.random()))
+            };
+            SkipNode::push_tail(&mut tails, node);
+            self.len += 1;
+        }
+        // 新元素已经用完，剩余的旧节点整体拼接到末尾
+        self.head.append(self.len, &mut old, old_len);
+        self.len += old_len;
+    }
+
+    /// 将 `other` 中的所有元素移动到 skiplist 的尾部，`other` 变为空。
+    ///
+    /// 两条链在各层末尾直接拼接，不会逐个重新插入。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let mut other: SkipList<_> = (5..10).collect();
+    /// skiplist.append(&mut other);
+    /// assert!(other.is_empty());
+    /// assert!(skiplist.iter().copied().eq(0..10));
+    ///
+    /// // 拼接处的反向链接同样被维护
+    /// assert!(skiplist.iter().rev().eq(skiplist.iter().collect::<Vec<_>>().into_iter().rev()));
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        if other.head.level > self.head.level {
+            // `other` 的头节点更高，用一个足够高的新头节点容纳两条链
+            let mut head = Box::new(SkipNode::head(other.head.level + 1));
+            head.append(0, &mut self.head, self.len);
+            head.append(self.len, &mut other.head, other.len);
+            self.head = head;
+        } else {
+            self.head.append(self.len, &mut other.head, other.len);
+        }
+        self.len += other.len;
+        other.len = 0;
+    }
+
+    /// 把 `iter` 中的元素依次追加到 skiplist 末尾。
+    ///
+    /// 新元素先在一个单独的头节点后连成一条链，再一次性拼接到末尾，
+    /// 不需要像逐个 [`push_back`](SkipList::push_back) 那样为每个元素从头节点下降。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut pushed: SkipList<_> = (0..10).collect();
+    /// let mut appended = pushed.clone();
+    /// for i in 0..1000 {
+    ///     pushed.push_back(i);
+    /// }
+    /// appended.append_iter(0..1000);
+    /// assert_eq!(appended.len(), 1010);
+    /// assert!(appended.iter().eq(pushed.iter()));
+    /// assert!(appended.iter().rev().eq(pushed.iter().rev()));
+    /// assert_eq!(appended.get(500), Some(&490));
+    /// ```
+    pub fn append_iter<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        let (mut back, added) = self.detached_chain(iter);
+        self.head.append(self.len, &mut back, added);
+        self.len += added;
+    }
+
+    /// 在给定索引处将 skiplist 一分为二，返回 `[at, len)` 内的元素，自身保留 `[0, at)`。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `at` 大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// let tail = skiplist.split_off(6);
+    /// assert!(skiplist.iter().copied().eq(0..6));
+    /// assert!(tail.iter().copied().eq(6..10));
+    /// assert!(tail.iter().rev().copied().eq((6..10).rev()));
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.len {
+            index_out_of_bounds(at, self.len);
+        }
+        let head = self.head.split_off(at);
+        let len = self.len - at;
+        self.len = at;
+        SkipList {
+            head,
+            len,
+            level_generator: self.level_generator.clone(),
+            auto_shrink: self.auto_shrink,
+        }
+    }
+
+    /// 消耗 skiplist，只保留前 `n` 个元素；`n` 不小于长度时保留全部元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert!(skiplist.take(3).iter().eq([0, 1, 2].iter()));
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.take(20).len(), 10);
+    /// ```
+    pub fn take(mut self, n: usize) -> SkipList<T> {
+        if n < self.len {
+            drop(self.split_off(n));
+        }
+        self
+    }
+
+    /// 消耗 skiplist，跳过前 `n` 个元素并返回其余元素；`n` 不小于长度时返回空的 skiplist。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert!(skiplist.skip(7).iter().eq([7, 8, 9].iter()));
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert!(skiplist.skip(20).is_empty());
+    /// ```
+    pub fn skip(mut self, n: usize) -> SkipList<T> {
+        let n = cmp::min(n, self.len);
+        self.split_off(n)
+    }
+
     /// Provides a reference to the element at the given index, or `None` if the
     /// skiplist is empty or the index is out of bounds.
     ///
@@ -177,32 +715,2231 @@ impl<T> SkipList<T> {
             .and_then(|node| node.item.as_mut())
     }
 
-    /// Removes and returns an element with the given index.
-    ///
-    /// # Panics
+    /// 返回索引 `index` 处元素的副本，索引越界时返回 `None`。
     ///
-    /// Panics is the index is out of bounds.
+    /// 适用于不能跨越锁持有引用的场景。
     ///
     /// # Examples
     ///
     /// ```
     /// use skiplist::SkipList;
     ///
-    /// let mut skiplist = SkipList::new();
-    /// skiplist.extend(0..10);
-    /// assert_eq!(skiplist.remove(4), 4);
-    /// assert_eq!(skiplist.remove(4), 5);
+    /// let skiplist: SkipList<_> = ["a", "b", "c"].into_iter().map(String::from).collect();
+    /// assert_eq!(skiplist.get_cloned(1).as_ref(), skiplist.get(1));
+    /// assert_eq!(skiplist.get_cloned(3), None);
     /// ```
-    pub fn remove(&mut self, index: usize) -> T {
-        if index >= self.len() {
-            panic!("Index out of bounds.");
-        } else {
-            let node = self.head.remove_at(index).unwrap();
-            self.len -= 1;
-            node.into_inner().unwrap()
-        }
+    pub fn get_cloned(&self, index: usize) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get(index).cloned()
     }
 
+    /// 返回第一个元素的副本，skiplist 为空时返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (1..4).collect();
+    /// assert_eq!(skiplist.first_cloned(), Some(1));
+    /// assert_eq!(SkipList::<i32>::new().first_cloned(), None);
+    /// ```
+    pub fn first_cloned(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get_cloned(0)
+    }
+
+    /// 返回最后一个元素的副本，skiplist 为空时返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (1..4).collect();
+    /// assert_eq!(skiplist.last_cloned(), Some(3));
+    /// assert_eq!(SkipList::<i32>::new().last_cloned(), None);
+    /// ```
+    pub fn last_cloned(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.get_cloned(self.len.checked_sub(1)?)
+    }
+
+    /// 返回最后一个元素的可变引用，skiplist 为空时返回 `None`。
+    ///
+    /// 节点只保存前向的层级链接，因此沿着塔结构下降到末尾，耗时 `O(log n)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// if let Some(last) = skiplist.last_mut() {
+    ///     *last = 100;
+    /// }
+    /// assert_eq!(skiplist.get(9), Some(&100));
+    /// assert_eq!(skiplist.len(), 10);
+    ///
+    /// let mut empty: SkipList<i32> = SkipList::new();
+    /// assert!(empty.last_mut().is_none());
+    /// ```
+    #[inline]
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        let index = self.len.checked_sub(1)?;
+        self.get_mut(index)
+    }
+
+    /// 返回索引 `index` 处元素的引用，不检查索引是否越界。
+    ///
+    /// # Safety
+    ///
+    /// 调用方必须保证 `index < self.len()`，否则行为未定义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// for i in 0..skiplist.len() {
+    ///     assert_eq!(Some(unsafe { skiplist.get_unchecked(i) }), skiplist.get(i));
+    /// }
+    /// ```
+    #[inline]
+    pub unsafe fn get_unchecked(&self, index: usize) -> &T {
+        debug_assert!(index < self.len, "get_unchecked requires index < len");
+        let item = self
+            .head
+            .advance(index + 1)
+            .and_then(|node| node.item.as_ref());
+        // SAFETY: 调用方保证索引在范围内，因此一定能找到对应的节点
+        unsafe { item.unwrap_unchecked() }
+    }
+
+    /// 返回索引 `index` 处元素的可变引用，不检查索引是否越界。
+    ///
+    /// # Safety
+    ///
+    /// 调用方必须保证 `index < self.len()`，否则行为未定义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// unsafe { *skiplist.get_unchecked_mut(3) = 30 };
+    /// assert_eq!(skiplist.get(3), Some(&30));
+    /// ```
+    #[inline]
+    pub unsafe fn get_unchecked_mut(&mut self, index: usize) -> &mut T {
+        debug_assert!(index < self.len, "get_unchecked_mut requires index < len");
+        let item = self
+            .head
+            .advance_mut(index + 1)
+            .and_then(|node| node.item.as_mut());
+        // SAFETY: 同 `get_unchecked`
+        unsafe { item.unwrap_unchecked() }
+    }
+
+    /// 依次获取 `indices` 中每个索引处元素的引用，越界的索引对应 `None`。
+    ///
+    /// 每次都从上一个找到的节点出发向后查找，只有索引变小时才从头节点重新下降，
+    /// 因此 `indices` 升序时整体只需一次下降加上沿途的前进。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(
+    ///     skiplist.get_many(&[0, 3, 9, 20]),
+    ///     [Some(&0), Some(&3), Some(&9), None]
+    /// );
+    /// // 乱序和重复的索引同样可以
+    /// assert_eq!(
+    ///     skiplist.get_many(&[7, 2, 2, 11, 5]),
+    ///     [Some(&7), Some(&2), Some(&2), None, Some(&5)]
+    /// );
+    /// ```
+    pub fn get_many<'a>(&'a self, indices: &[usize]) -> Vec<Option<&'a T>> {
+        let mut finger: (&SkipNode<T>, usize) = (&self.head, 0);
+        indices
+            .iter()
+            .map(|&index| {
+                if index >= self.len {
+                    return None;
+                }
+                let (node, pos) = finger;
+                let node = if pos <= index + 1 {
+                    node.seek(index + 1 - pos)?
+                } else {
+                    self.head.advance(index + 1)?
+                };
+                finger = (node, index + 1);
+                node.item.as_ref()
+            })
+            .collect()
+    }
+
+    /// 同时获取两个不同索引处元素的可变引用。
+    ///
+    /// 如果 `i == j` 或任意一个索引越界，则返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// if let Some((a, b)) = skiplist.get_pair_mut(4, 1) {
+    ///     *a += *b;
+    ///     *b = 0;
+    /// }
+    /// assert!(skiplist.iter().eq([0, 0, 2, 3, 5].iter()));
+    /// assert!(skiplist.get_pair_mut(2, 2).is_none());
+    /// assert!(skiplist.get_pair_mut(0, 5).is_none());
+    /// ```
+    pub fn get_pair_mut(&mut self, i: usize, j: usize) -> Option<(&mut T, &mut T)> {
+        if i == j || i >= self.len || j >= self.len {
+            return None;
+        }
+        // 先定位靠后的节点：到达靠前节点的下降路径不会经过它，因此不会使它的指针失效
+        let (mut first, mut second) = if i < j {
+            let second = NonNull::from(self.head.advance_mut(j + 1)?);
+            (NonNull::from(self.head.advance_mut(i + 1)?), second)
+        } else {
+            let first = NonNull::from(self.head.advance_mut(i + 1)?);
+            (first, NonNull::from(self.head.advance_mut(j + 1)?))
+        };
+        // SAFETY: `i != j`，两个指针指向不同的节点，且 `&mut self` 保证了独占访问
+        unsafe {
+            Some((
+                first.as_mut().item.as_mut()?,
+                second.as_mut().item.as_mut()?,
+            ))
+        }
+    }
+
+    /// 找到 `a` 和 `b` 第一次出现的位置并交换这两个位置上的元素。
+    ///
+    /// 任意一个值不存在时返回 `false`，skiplist 保持不变。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = ["alice", "bob", "carol", "dave"].into_iter().collect();
+    /// assert!(skiplist.swap_values(&"bob", &"dave"));
+    /// assert!(skiplist.iter().eq(["alice", "dave", "carol", "bob"].iter()));
+    /// assert!(!skiplist.swap_values(&"alice", &"eve"));
+    /// ```
+    pub fn swap_values(&mut self, a: &T, b: &T) -> bool
+    where
+        T: PartialEq,
+    {
+        let (mut i, mut j) = (None, None);
+        for (index, item) in self.iter().enumerate() {
+            if i.is_none() && item == a {
+                i = Some(index);
+            }
+            if j.is_none() && item == b {
+                j = Some(index);
+            }
+            if i.is_some() && j.is_some() {
+                break;
+            }
+        }
+        match (i, j) {
+            (Some(i), Some(j)) => {
+                if let Some((x, y)) = self.get_pair_mut(i, j) {
+                    mem::swap(x, y);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 把所有等于 `old` 的元素替换为 `new` 的副本，返回被替换的个数。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = [2, 1, 2, 3, 2].into_iter().collect();
+    /// assert_eq!(skiplist.replace_all(&2, 9), 3);
+    /// assert!(skiplist.iter().eq([9, 1, 9, 3, 9].iter()));
+    /// ```
+    pub fn replace_all(&mut self, old: &T, new: T) -> usize
+    where
+        T: PartialEq + Clone,
+    {
+        let mut count = 0;
+        for item in self.iter_mut() {
+            if item == old {
+                item.clone_from(&new);
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// 用 `value` 替换索引 `index` 处的元素并返回旧值，不改变长度和层级结构。
+    ///
+    /// # Panics
+    ///
+    /// 如果索引越界，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// assert_eq!(skiplist.set(2, 10), 2);
+    /// assert!(skiplist.iter().eq([0, 1, 10, 3, 4].iter()));
+    /// ```
+    pub fn set(&mut self, index: usize, value: T) -> T {
+        match self.get_mut(index) {
+            Some(item) => mem::replace(item, value),
+            None => index_out_of_bounds(index, self.len),
+        }
+    }
+
+    /// Removes and returns an element with the given index.
+    ///
+    /// # Panics
+    ///
+    /// Panics is the index is out of bounds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..10);
+    /// assert_eq!(skiplist.remove(4), 4);
+    /// assert_eq!(skiplist.remove(4), 5);
+    ///
+    /// let result = std::panic::catch_unwind(move || skiplist.remove(12));
+    /// let message = result.unwrap_err().downcast::<String>().unwrap();
+    /// assert_eq!(*message, "index 12 out of bounds for skiplist of length 8");
+    /// ```
+    pub fn remove(&mut self, index: usize) -> T {
+        if index >= self.len() {
+            index_out_of_bounds(index, self.len);
+        } else {
+            let node = self.head.remove_at(index).unwrap();
+            self.len -= 1;
+            node.into_inner().unwrap()
+        }
+    }
+
+    /// 移除并返回第一个元素；skiplist 为空时返回 `None`。
+    ///
+    /// 第一个节点的前驱都是头节点，因此只需 `O(层数)` 的时间。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..3).collect();
+    /// assert_eq!(skiplist.remove_first(), Some(0));
+    /// assert_eq!(skiplist.remove_first(), Some(1));
+    /// assert_eq!(skiplist.remove_first(), Some(2));
+    /// assert_eq!(skiplist.remove_first(), None);
+    /// assert!(skiplist.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove_first(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(0))
+        }
+    }
+
+    /// 移除并返回最后一个元素；skiplist 为空时返回 `None`。
+    ///
+    /// 需要 `O(log n)` 的时间找到最后一个节点的前驱。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..3).collect();
+    /// assert_eq!(skiplist.remove_last(), Some(2));
+    /// skiplist.push_back(5);
+    /// assert_eq!(skiplist.remove_last(), Some(5));
+    /// assert_eq!(skiplist.remove_last(), Some(1));
+    /// assert_eq!(skiplist.remove_last(), Some(0));
+    /// assert_eq!(skiplist.remove_last(), None);
+    /// assert!(skiplist.is_empty());
+    /// ```
+    #[inline]
+    pub fn remove_last(&mut self) -> Option<T> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.remove(self.len - 1))
+        }
+    }
+
+    /// 将索引 `index` 处的元素移动到 skiplist 的头部，其余元素依次后移。
+    ///
+    /// 节点本身不会被重新分配，只是从原位置断开后重新链接到头节点之后。
+    ///
+    /// # Panics
+    ///
+    /// 如果索引越界，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..5);
+    /// skiplist.move_to_front(3);
+    /// assert!(skiplist.iter().eq([3, 0, 1, 2, 4].iter()));
+    /// ```
+    pub fn move_to_front(&mut self, index: usize) {
+        if index >= self.len() {
+            index_out_of_bounds(index, self.len);
+        }
+        let node = self.head.remove_at(index).unwrap();
+        self.head
+            .insert_at(node, 0)
+            .unwrap_or_else(|_| panic!("No insertion position is found!"));
+    }
+
+    /// 用切片 `src` 中的值依次覆盖 skiplist 中的元素，只需一次第 0 层遍历。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `src` 的长度与 skiplist 的长度不同，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..5);
+    /// skiplist.copy_from_slice(&[5, 6, 7, 8, 9]);
+    /// assert!(skiplist.iter().eq([5, 6, 7, 8, 9].iter()));
+    /// ```
+    pub fn copy_from_slice(&mut self, src: &[T])
+    where
+        T: Clone,
+    {
+        if src.len() != self.len() {
+            panic!("Source slice length does not match the skiplist length.");
+        }
+        for (dst, value) in self.iter_mut().zip(src) {
+            dst.clone_from(value);
+        }
+    }
+
+    /// 只保留满足 `f` 的元素，按顺序对每个元素调用一次 `f`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// skiplist.retain(|&x| x % 3 == 0);
+    /// assert!(skiplist.iter().eq([0, 3, 6, 9].iter()));
+    /// ```
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        self.retain_count(f);
+    }
+
+    /// 与 [`retain`](SkipList::retain) 相同，并返回被移除的元素个数。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.retain_count(|&x| x % 2 == 0), 5);
+    /// assert!(skiplist.iter().eq([0, 2, 4, 6, 8].iter()));
+    /// ```
+    pub fn retain_count<F>(&mut self, mut f: F) -> usize
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let len = self.len;
+        self.head.retain(&mut self.len, |_, current| f(current));
+        self.shrink_if_sparse();
+        len - self.len
+    }
+
+    /// 在排好序的 skiplist 中移除所有等于 `values` 中某个值的元素，返回被移除的元素个数。
+    ///
+    /// `values` 同样需要排好序。两者只需一次合并遍历，而不必为每个值分别从头节点查找。
+    /// 如果 skiplist 或 `values` 没有排序，被移除的元素没有意义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (1..=5).collect();
+    /// assert_eq!(skiplist.remove_values(&[2, 4]), 2);
+    /// assert!(skiplist.iter().eq([1, 3, 5].iter()));
+    ///
+    /// // 重复的元素全部被移除，不存在的值被跳过
+    /// let mut skiplist: SkipList<_> = [1, 2, 2, 3, 3, 3].into_iter().collect();
+    /// assert_eq!(skiplist.remove_values(&[0, 3, 4]), 3);
+    /// assert!(skiplist.iter().eq([1, 2, 2].iter()));
+    /// ```
+    pub fn remove_values(&mut self, values: &[T]) -> usize
+    where
+        T: Ord,
+    {
+        let mut values = values.iter().peekable();
+        self.retain_count(|current| {
+            while values.next_if(|&value| value < current).is_some() {}
+            values.peek() != Some(&current)
+        })
+    }
+
+    /// 只保留索引位于 `range` 内的元素，其余元素被释放。
+    ///
+    /// 两端各只需一次结构上的切分，不需要逐个检查保留的元素。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `range.start > range.end` 或 `range.end` 大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// skiplist.retain_range(3..7);
+    /// assert!(skiplist.iter().eq([3, 4, 5, 6].iter()));
+    /// ```
+    pub fn retain_range(&mut self, range: ops::Range<usize>) {
+        let ops::Range { start, end } = range;
+        if start > end || end > self.len {
+            range_out_of_bounds(start, end, self.len);
+        }
+        drop(self.split_off(end));
+        let kept = self.split_off(start);
+        drop(mem::replace(self, kept));
+        self.shrink_if_sparse();
+    }
+
+    /// 移除索引位于 `range` 内的元素，并在该位置依次插入 `with` 中的元素。
+    ///
+    /// 与 `Vec::splice` 类似，但不返回被移除的元素；插入的元素个数可以与区间长度不同。
+    /// 被移除的部分和插入的部分都只需一次结构上的切分或拼接。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `range.start > range.end` 或 `range.end` 大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..6).collect();
+    /// skiplist.replace_range(2..4, [10, 11, 12]);
+    /// assert_eq!(skiplist, [0, 1, 10, 11, 12, 4, 5][..]);
+    ///
+    /// skiplist.replace_range(1..6, [7]);
+    /// assert_eq!(skiplist, [0, 7, 5][..]);
+    ///
+    /// // 空区间相当于在该位置插入
+    /// skiplist.replace_range(3..3, [8, 9]);
+    /// assert_eq!(skiplist, [0, 7, 5, 8, 9][..]);
+    /// ```
+    pub fn replace_range<I>(&mut self, range: ops::Range<usize>, with: I)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        drop(self.splice(range, with));
+    }
+
+    /// 用 `replace_with` 中的元素替换索引位于 `range` 内的元素，返回产生被移除元素的迭代器。
+    ///
+    /// 与 `Vec::splice` 类似，替换在返回的 [`Splice`] 被丢弃时才完成：
+    /// `replace_with` 在调用时被收集为一条单独的链，丢弃时再与 `range` 之后的元素依次拼接回来，
+    /// 没有被取出的被移除元素随之释放。如果 [`Splice`] 被泄漏，skiplist 只保留 `range` 之前的元素。
+    /// 插入的元素个数可以与区间长度不同。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `range.start > range.end` 或 `range.end` 大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..6).collect();
+    /// let removed: Vec<_> = skiplist.splice(1..4, [10, 11]).collect();
+    /// assert_eq!(removed, [1, 2, 3]);
+    /// assert_eq!(skiplist, [0, 10, 11, 4, 5][..]);
+    ///
+    /// let mut splice = skiplist.splice(3..5, []);
+    /// assert_eq!(splice.len(), 2);
+    /// assert_eq!(splice.next_back(), Some(5));
+    /// drop(splice);
+    /// assert_eq!(skiplist, [0, 10, 11][..]);
+    ///
+    /// let result = std::panic::catch_unwind(move || skiplist.splice(2..4, [1]).count());
+    /// let message = result.unwrap_err().downcast::<String>().unwrap();
+    /// assert_eq!(*message, "range 2..4 out of bounds for skiplist of length 3");
+    /// ```
+    pub fn splice<I>(&mut self, range: ops::Range<usize>, replace_with: I) -> Splice<'_, T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let ops::Range { start, end } = range;
+        if start > end || end > self.len {
+            range_out_of_bounds(start, end, self.len);
+        }
+        let mut replacement = self.empty_like();
+        replacement.append_iter(replace_with);
+        // 新节点的层级取自克隆的生成器，把推进后的状态换回来
+        mem::swap(&mut self.level_generator, &mut replacement.level_generator);
+        let tail = self.split_off(end);
+        let removed = self.split_off(start).into_iter();
+        Splice {
+            list: self,
+            removed,
+            replacement,
+            tail,
+        }
+    }
+
+    /// 把所有元素向尾部移动 `n` 个位置，长度保持不变。
+    ///
+    /// 末尾的 `n` 个元素被丢弃，头部补上 `n` 个 `T::default()`。
+    /// `n` 不小于长度时，所有元素都被替换为默认值。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (1..4).collect();
+    /// skiplist.shift_right(1);
+    /// assert_eq!(skiplist, [0, 1, 2][..]);
+    ///
+    /// skiplist.shift_right(5);
+    /// assert_eq!(skiplist, [0, 0, 0][..]);
+    /// ```
+    pub fn shift_right(&mut self, n: usize)
+    where
+        T: Default,
+    {
+        let n = cmp::min(n, self.len);
+        drop(self.split_off(self.len - n));
+        self.extend_front(iter::repeat_with(T::default).take(n));
+    }
+
+    /// 把所有元素向头部移动 `n` 个位置，长度保持不变。
+    ///
+    /// 开头的 `n` 个元素被丢弃，尾部补上 `n` 个 `T::default()`。
+    /// `n` 不小于长度时，所有元素都被替换为默认值。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (1..4).collect();
+    /// skiplist.shift_left(1);
+    /// assert_eq!(skiplist, [2, 3, 0][..]);
+    ///
+    /// skiplist.shift_left(0);
+    /// assert_eq!(skiplist, [2, 3, 0][..]);
+    /// ```
+    pub fn shift_left(&mut self, n: usize)
+    where
+        T: Default,
+    {
+        let n = cmp::min(n, self.len);
+        let kept = self.split_off(n);
+        drop(mem::replace(self, kept));
+        self.extend(iter::repeat_with(T::default).take(n));
+    }
+
+    /// 创建一个惰性地移除并返回所有满足 `f` 的元素的迭代器，其余元素按原顺序保留。
+    ///
+    /// 迭代器被丢弃时，会先继续移除剩余的满足 `f` 的元素。
+    /// 如果迭代器被泄漏，skiplist 会表现为空，但仍然有效。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// let evens: Vec<_> = skiplist.extract_if(|x| *x % 2 == 0).collect();
+    /// assert_eq!(evens, [0, 2, 4, 6, 8]);
+    /// assert!(skiplist.iter().eq([1, 3, 5, 7, 9].iter()));
+    ///
+    /// // 提前丢弃迭代器，剩余的匹配元素也会被移除
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.extract_if(|x| *x >= 5).next(), Some(5));
+    /// assert!(skiplist.iter().eq([0, 1, 2, 3, 4].iter()));
+    /// ```
+    pub fn extract_if<F>(&mut self, f: F) -> ExtractIf<'_, T, F>
+    where
+        F: FnMut(&mut T) -> bool,
+    {
+        ExtractIf {
+            pass: self.head.retain_pass(&mut self.len),
+            pred: f,
+            panicked: false,
+        }
+    }
+
+    /// 移除所有元素，返回按顺序产生这些元素的迭代器。
+    ///
+    /// 与 [`clear`](SkipList::clear) 一样，头节点的层数保持不变，清空后的 skiplist 可以继续使用。
+    /// skiplist 在调用后立即为空；迭代器被丢弃时，尚未产生的元素一并释放。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(1024);
+    /// skiplist.extend(0..10);
+    /// let drained: Vec<_> = skiplist.drain_all().collect();
+    /// assert_eq!(drained, (0..10).collect::<Vec<_>>());
+    /// assert!(skiplist.is_empty());
+    /// assert_eq!(skiplist.capacity(), 1024);
+    ///
+    /// skiplist.extend(10..15);
+    /// let mut drain = skiplist.drain_all();
+    /// assert_eq!(drain.len(), 5);
+    /// assert_eq!(drain.next_back(), Some(14));
+    /// assert_eq!(drain.next(), Some(10));
+    /// drop(drain);
+    /// assert!(skiplist.is_empty());
+    ///
+    /// skiplist.push_back(1);
+    /// assert!(skiplist.iter().eq([1].iter()));
+    /// ```
+    pub fn drain_all(&mut self) -> Drain<'_, T> {
+        let empty = Box::new(SkipNode::head(self.head_level()));
+        let head = mem::replace(&mut self.head, empty);
+        let size = mem::replace(&mut self.len, 0);
+        Drain {
+            iter: IntoIter { head, size },
+            _marker: PhantomData,
+        }
+    }
+
+    /// 移除连续的、映射到相同键的元素，只保留其中第一个。
+    ///
+    /// 只需一次第 0 层遍历，同时重建各层链接。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend([1, 11, 21, 2, 12, 3, 13, 1]);
+    /// skiplist.dedup_by_key(|x| x % 10);
+    /// assert!(skiplist.iter().eq([1, 2, 3, 1].iter()));
+    /// ```
+    pub fn dedup_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        self.head.retain(&mut self.len, |prev, current| match prev {
+            Some(prev) => key(prev) != key(current),
+            None => true,
+        });
+        self.shrink_if_sparse();
+    }
+
+    /// 消耗 skiplist，移除连续的相等元素，只保留其中第一个。
+    ///
+    /// 对排好序的 skiplist，结果中每个值只出现一次；未排序时不相邻的重复元素都会保留。
+    /// 节点被原地移除，不会重新分配。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 1, 2, 3, 3].into_iter().collect();
+    /// assert!(skiplist.into_dedup().iter().eq([1, 2, 3].iter()));
+    ///
+    /// let skiplist: SkipList<_> = [1, 1, 2, 1, 1].into_iter().collect();
+    /// assert!(skiplist.into_dedup().iter().eq([1, 2, 1].iter()));
+    /// ```
+    pub fn into_dedup(mut self) -> SkipList<T>
+    where
+        T: PartialEq,
+    {
+        self.head
+            .retain(&mut self.len, |prev, current| prev != Some(current));
+        self.shrink_if_sparse();
+        self
+    }
+
+    /// 在按键排好序的 skiplist 中二分查找键为 `b` 的元素，行为与
+    /// [`slice::binary_search_by_key`] 相同。
+    ///
+    /// 查找通过逐层下降完成，复杂度为 O(log n)。如果 skiplist 没有按 `f` 的结果排序，
+    /// 返回值没有意义。
+    ///
+    /// 找到时返回 `Ok(index)`，存在多个匹配时返回第一个；否则返回 `Err(index)`，
+    /// 其中 `index` 是保持顺序的插入位置。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// struct Item {
+    ///     id: u32,
+    ///     name: &'static str,
+    /// }
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend([
+    ///     Item { id: 1, name: "a" },
+    ///     Item { id: 3, name: "b" },
+    ///     Item { id: 5, name: "c" },
+    /// ]);
+    /// assert_eq!(skiplist.binary_search_by_key(&3, |item| item.id), Ok(1));
+    /// assert_eq!(skiplist.binary_search_by_key(&4, |item| item.id), Err(2));
+    /// assert_eq!(skiplist.binary_search_by_key(&9, |item| item.id), Err(3));
+    /// assert_eq!(skiplist.get(1).map(|item| item.name), Some("b"));
+    /// ```
+    pub fn binary_search_by_key<B, F>(&self, b: &B, mut f: F) -> Result<usize, usize>
+    where
+        B: Ord,
+        F: FnMut(&T) -> B,
+    {
+        let (node, index) = self.head.find_last(|value| f(value) < *b);
+        match node.next_ref().and_then(|next| next.item.as_ref()) {
+            Some(value) if f(value) == *b => Ok(index),
+            _ => Err(index),
+        }
+    }
+
+    /// 在排好序的 skiplist 中返回所有等于 `value` 的元素的索引范围，通过两次逐层下降完成。
+    ///
+    /// 不存在相等的元素时返回一个空范围，其起点是保持顺序的插入位置。
+    /// 如果 skiplist 没有排序，返回值没有意义。
+    /// 只需判断是否存在时，可以使用只下降一次的 [`contains_sorted`](SkipList::contains_sorted)。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 2, 2, 2, 3].into_iter().collect();
+    /// assert_eq!(skiplist.equal_range(&2), 1..4);
+    /// assert_eq!(skiplist.equal_range(&0), 0..0);
+    /// assert_eq!(skiplist.equal_range(&10), 5..5);
+    /// ```
+    pub fn equal_range(&self, value: &T) -> ops::Range<usize>
+    where
+        T: Ord,
+    {
+        let (_, start) = self.head.find_last(|item| item < value);
+        let (_, end) = self.head.find_last(|item| item <= value);
+        start..end
+    }
+
+    /// 在排好序的 skiplist 中返回第一个大于 `key` 的元素；`inclusive` 为 `true` 时包括等于 `key` 的元素。
+    ///
+    /// 通过一次逐层下降完成。如果 skiplist 没有排序，返回值没有意义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 3, 5].into_iter().collect();
+    /// assert_eq!(skiplist.first_after(&3, true), Some(&3));
+    /// assert_eq!(skiplist.first_after(&3, false), Some(&5));
+    /// assert_eq!(skiplist.first_after(&0, false), Some(&1));
+    /// assert_eq!(skiplist.first_after(&5, true), Some(&5));
+    /// assert_eq!(skiplist.first_after(&5, false), None);
+    /// ```
+    pub fn first_after(&self, key: &T, inclusive: bool) -> Option<&T>
+    where
+        T: Ord,
+    {
+        let (node, _) = if inclusive {
+            self.head.find_last(|item| item < key)
+        } else {
+            self.head.find_last(|item| item <= key)
+        };
+        node.next_ref().and_then(|next| next.item.as_ref())
+    }
+
+    /// 在排好序的 skiplist 中返回最后一个小于 `key` 的元素；`inclusive` 为 `true` 时包括等于 `key` 的元素。
+    ///
+    /// 通过一次逐层下降完成。如果 skiplist 没有排序，返回值没有意义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 3, 5].into_iter().collect();
+    /// assert_eq!(skiplist.last_before(&3, true), Some(&3));
+    /// assert_eq!(skiplist.last_before(&3, false), Some(&1));
+    /// assert_eq!(skiplist.last_before(&6, false), Some(&5));
+    /// assert_eq!(skiplist.last_before(&1, true), Some(&1));
+    /// assert_eq!(skiplist.last_before(&1, false), None);
+    /// ```
+    pub fn last_before(&self, key: &T, inclusive: bool) -> Option<&T>
+    where
+        T: Ord,
+    {
+        let (node, _) = if inclusive {
+            self.head.find_last(|item| item <= key)
+        } else {
+            self.head.find_last(|item| item < key)
+        };
+        node.item.as_ref()
+    }
+
+    /// 在排好序的 skiplist 中，只有不存在相等的元素时才按顺序插入 `value`。
+    ///
+    /// 插入时返回 `true`，已经存在时返回 `false` 且 skiplist 保持不变。
+    /// 检查和插入共用同一次逐层下降。如果 skiplist 没有排序，插入位置没有意义。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = [1, 3, 5].into_iter().collect();
+    /// assert!(skiplist.insert_if_absent(4));
+    /// assert!(!skiplist.insert_if_absent(3));
+    /// assert!(skiplist.insert_if_absent(0));
+    /// assert!(skiplist.iter().eq([0, 1, 3, 4, 5].iter()));
+    /// ```
+    pub fn insert_if_absent(&mut self, value: T) -> bool
+    where
+        T: Ord,
+    {
+        let new_node = Box::new(SkipNode::new(value, self.level_generator.random()));
+        match self.head.insert_sorted_unique(new_node) {
+            Ok(_) => {
+                self.len += 1;
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// 消耗 skiplist，按 `f` 的结果把元素分到两个 skiplist 中：
+    /// 第一个包含 `f` 返回 `true` 的元素，第二个包含其余元素，两者都保持原有顺序。
+    ///
+    /// 节点被直接移动到新的链表中，不会重新分配。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// let (even, odd) = skiplist.partition(|x| x % 2 == 0);
+    /// assert_eq!(even.len(), 5);
+    /// assert_eq!(odd.len(), 5);
+    /// assert!(even.iter().eq([0, 2, 4, 6, 8].iter()));
+    /// assert!(odd.iter().eq([1, 3, 5, 7, 9].iter()));
+    /// ```
+    pub fn partition<F>(mut self, mut f: F) -> (SkipList<T>, SkipList<T>)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut matched = self.empty_like();
+        let mut unmatched = self.empty_like();
+        let mut matched_tails = matched.head.tails(0);
+        let mut unmatched_tails = unmatched.head.tails(0);
+        while let Some(node) = self.head.remove_at(0) {
+            if f(node.item.as_ref().unwrap()) {
+                SkipNode::push_tail(&mut matched_tails, node);
+                matched.len += 1;
+            } else {
+                SkipNode::push_tail(&mut unmatched_tails, node);
+                unmatched.len += 1;
+            }
+        }
+        (matched, unmatched)
+    }
+
+    /// 消耗 skiplist，对每个元素调用 `f`，按原有顺序组成一个新的 skiplist。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..5).collect();
+    /// let doubled = skiplist.map(|x| x * 2);
+    /// assert!(doubled.iter().eq([0, 2, 4, 6, 8].iter()));
+    /// ```
+    pub fn map<U, F>(self, f: F) -> SkipList<U>
+    where
+        F: FnMut(T) -> U,
+    {
+        let mut mapped = SkipList::with_capacity(self.len());
+        mapped.extend(self.into_iter().map(f));
+        mapped
+    }
+
+    /// 消耗两个 skiplist，把对应位置的元素组成元组，在较短的一个结束时停止。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let numbers: SkipList<_> = [1, 2, 3].into_iter().collect();
+    /// let letters: SkipList<_> = ['a', 'b'].into_iter().collect();
+    /// let zipped = numbers.zip(letters);
+    /// assert!(zipped.iter().eq([(1, 'a'), (2, 'b')].iter()));
+    /// ```
+    pub fn zip<U>(self, other: SkipList<U>) -> SkipList<(T, U)> {
+        let mut zipped = SkipList::with_capacity(cmp::min(self.len(), other.len()));
+        zipped.extend(self.into_iter().zip(other));
+        zipped
+    }
+
+    /// 消耗两个 skiplist，轮流从两者中取出元素，较长一个剩余的元素依次追加在末尾。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let odd: SkipList<_> = [1, 3, 5].into_iter().collect();
+    /// let even: SkipList<_> = [2, 4].into_iter().collect();
+    /// let merged = odd.interleave(even);
+    /// assert!(merged.iter().eq([1, 2, 3, 4, 5].iter()));
+    /// ```
+    pub fn interleave(self, other: SkipList<T>) -> SkipList<T> {
+        let mut merged = SkipList::with_capacity(self.len() + other.len());
+        let mut left = self.into_iter();
+        let mut right = other.into_iter();
+        loop {
+            match (left.next(), right.next()) {
+                (Some(a), Some(b)) => {
+                    merged.push_back(a);
+                    merged.push_back(b);
+                }
+                (Some(a), None) => {
+                    merged.push_back(a);
+                    merged.extend(left);
+                    break;
+                }
+                (None, Some(b)) => {
+                    merged.push_back(b);
+                    merged.extend(right);
+                    break;
+                }
+                (None, None) => break,
+            }
+        }
+        merged
+    }
+
+    /// 消耗 skiplist，返回一个排好序的 `Vec`，元素被直接移出而不会被克隆。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend([3, 1, 4, 1, 5]);
+    /// assert_eq!(skiplist.into_sorted_vec(), vec![1, 1, 3, 4, 5]);
+    /// ```
+    pub fn into_sorted_vec(self) -> Vec<T>
+    where
+        T: Ord,
+    {
+        let mut vec: Vec<T> = self.into_iter().collect();
+        vec.sort();
+        vec
+    }
+
+    /// 在排好序的 skiplist 中判断是否存在等于 `value` 的元素。
+    ///
+    /// 通过一次逐层下降完成，耗时 O(log n)。如果 skiplist 没有排序，返回值没有意义；
+    /// 未排序时应使用 `iter().any(|x| x == value)` 逐个比较。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..500).map(|x| x * 2).collect();
+    /// assert!(skiplist.contains_sorted(&0));
+    /// assert!(skiplist.contains_sorted(&424));
+    /// assert!(skiplist.contains_sorted(&998));
+    /// assert!(!skiplist.contains_sorted(&425));
+    /// assert!(!skiplist.contains_sorted(&-1));
+    /// assert!(!skiplist.contains_sorted(&1000));
+    ///
+    /// assert!(!SkipList::new().contains_sorted(&0));
+    /// ```
+    pub fn contains_sorted(&self, value: &T) -> bool
+    where
+        T: Ord,
+    {
+        let (node, _) = self.head.find_last(|item| item < value);
+        node.next_ref().and_then(|next| next.item.as_ref()) == Some(value)
+    }
+
+    /// 按确定性的均衡布局重建所有节点的层级，元素的顺序和值保持不变。
+    ///
+    /// 重建后第 `i` 个节点（从 1 开始计数）的层级为 `i` 末尾 0 的位数，
+    /// 即每隔一个节点升高一层，使得每次下降在每一层最多只需走两步。
+    /// 节点本身被复用，不会重新分配。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// // 晋升概率很低时，随机生成的高层节点很少
+    /// let mut skiplist = SkipList::with_capacity_and_probability(256, 0.1);
+    /// skiplist.extend(0..256);
+    /// let top = skiplist.head_level() - 1;
+    /// let before = skiplist.level_histogram();
+    ///
+    /// skiplist.compact();
+    /// assert!(skiplist.iter().copied().eq(0..256));
+    ///
+    /// // 重建后恰好一半的节点位于第 1 层及以上，最高层也有节点
+    /// let after = skiplist.level_histogram();
+    /// assert_eq!(after[..3], [128, 64, 32]);
+    /// assert!(before[1..].iter().sum::<usize>() < after[1..].iter().sum::<usize>());
+    /// assert!(before[top] < after[top]);
+    /// ```
+    pub fn compact(&mut self) {
+        let top = self.head.level;
+        let mut head = Box::new(SkipNode::head(top + 1));
+        let mut tails = head.tails(0);
+        let mut pos = 0;
+        while let Some(mut node) = self.head.remove_at(0) {
+            pos += 1;
+            let level = balanced_level(pos, top);
+            node.level = level;
+            node.links.resize(level + 1, None);
+            node.links_len.resize(level + 1, 0);
+            SkipNode::push_tail(&mut tails, node);
+        }
+        self.head = head;
+    }
+
+    /// 返回指向头节点的不透明指针，供外部代码（如 FFI 桥接层）沿第 0 层链接手动遍历。
+    ///
+    /// 头节点没有值，第一个元素位于 [`NodePtr::next`] 返回的节点。
+    /// 该指针以及由它得到的所有节点指针只在 skiplist 未被修改、也未被移动或释放期间有效；
+    /// 任何修改都可能释放或重新链接节点，之后再解引用是未定义行为。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..100).collect();
+    /// let mut values = Vec::new();
+    /// let head = skiplist.head_ptr();
+    /// let mut node = head;
+    /// // SAFETY: 遍历期间 skiplist 没有被修改、移动或释放
+    /// while let Some(next) = unsafe { node.next() } {
+    ///     node = next;
+    ///     values.push(*unsafe { node.item() }.unwrap());
+    /// }
+    /// assert_eq!(values, skiplist.iter().copied().collect::<Vec<_>>());
+    /// assert_eq!(unsafe { head.item() }, None);
+    /// ```
+    #[inline]
+    pub fn head_ptr(&self) -> NodePtr<T> {
+        NodePtr(NonNull::from(&*self.head))
+    }
+
+    /// 从头节点逐层下降到索引 `index` 处的节点，返回该节点的元素和沿途累加的跨度。
+    ///
+    /// 跨度正确时累加的跨度总是 `index + 1`（头节点位于位置 0），因此可以用来检查下降的计算。
+    /// 索引越界时返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..1000).collect();
+    /// for index in [0, 1, 2, 511, 512, 999] {
+    ///     let (value, span) = skiplist.locate(index).unwrap();
+    ///     assert_eq!(span, index + 1);
+    ///     assert_eq!(*value, index);
+    /// }
+    /// assert!(skiplist.locate(1000).is_none());
+    /// ```
+    pub fn locate(&self, index: usize) -> Option<(&T, usize)> {
+        if index >= self.len {
+            return None;
+        }
+        let target = index + 1;
+        let mut node: &SkipNode<T> = &self.head;
+        let mut span = 0;
+        for level in (0..=self.head.level).rev() {
+            while let Some(next) = node.links[level] {
+                if span + node.links_len[level] > target {
+                    break;
+                }
+                span += node.links_len[level];
+                // SAFETY: 链接指向链表中的有效节点，生命周期受 `&self` 约束
+                node = unsafe { next.as_ref() };
+            }
+        }
+        node.item.as_ref().map(|item| (item, span))
+    }
+
+    /// 以第 0 层链为准重新计算长度，并从头重建所有高层链接和跨度。
+    ///
+    /// 记录正确时结果与原来相同；用于在怀疑高层链接、跨度或长度与第 0 层链不一致时恢复，
+    /// 只要第 0 层链本身完好，修复后 skiplist 就重新可用。耗时 O(n)。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..100).collect();
+    /// skiplist.validate_and_repair();
+    /// assert_eq!(skiplist.len(), 100);
+    /// assert_eq!(skiplist.get(42), Some(&42));
+    /// assert!(skiplist.range_mut(10, 20).map(|x| *x).eq(10..20));
+    /// for level in 0..skiplist.head_level() {
+    ///     assert_eq!(skiplist.total_span(level), 100);
+    /// }
+    /// ```
+    pub fn validate_and_repair(&mut self) {
+        self.head.repair(&mut self.len);
+        let levels = self.head_level();
+        if self.level_generator.total() > levels {
+            self.level_generator.set_total(levels);
+        }
+    }
+
+    /// 从索引 `i` 处的节点出发，每一步沿不越过索引 `j` 的最高链接前进，累加实际跳过的跨度。
+    ///
+    /// 跨度正确时结果总是 `j - i`，并且最终到达的正是索引 `j` 处的节点，
+    /// 因此它主要用于检查各层的跨度记录。遇到更高的节点时会随之升高，耗时 O(log n)。
+    ///
+    /// # Panics
+    ///
+    /// 如果任意一个索引越界，或 `i > j`，则会恐慌。
+    /// 如果跳过的跨度没有到达索引 `j` 处的节点，说明跨度记录不一致，同样会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..100).collect();
+    /// skiplist.remove(10);
+    /// skiplist.insert(100, 50);
+    /// assert_eq!(skiplist.span_between(5, 80), 75);
+    /// assert_eq!(skiplist.span_between(5, 80), skiplist.iter().skip(5).take(75).count());
+    /// assert_eq!(skiplist.span_between(7, 7), 0);
+    /// assert_eq!(skiplist.span_between(0, 99), 99);
+    /// ```
+    pub fn span_between(&self, i: usize, j: usize) -> usize {
+        if j >= self.len {
+            index_out_of_bounds(j, self.len);
+        }
+        if i > j {
+            panic!("span start {} is after span end {}", i, j);
+        }
+        let target: *const SkipNode<T> = self.get_index(j).unwrap();
+        let mut node = self.get_index(i).unwrap();
+        let mut span = 0;
+        while span < j - i {
+            let level = (0..=node.level)
+                .rev()
+                .find(|&level| node.links[level].is_some() && span + node.links_len[level] <= j - i)
+                .expect("span bookkeeping is inconsistent");
+            span += node.links_len[level];
+            // SAFETY: 链接指向链表中的有效节点
+            node = unsafe { node.links[level].unwrap().as_ref() };
+        }
+        assert!(ptr::eq(node, target), "span bookkeeping is inconsistent");
+        span
+    }
+
+    /// 头节点的层数，即新节点最多可以达到的层数。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<i32> = SkipList::with_capacity(1024);
+    /// assert_eq!(skiplist.head_level(), 10);
+    /// ```
+    #[inline]
+    pub fn head_level(&self) -> usize {
+        self.head.links.len()
+    }
+
+    /// 确保之后插入的节点最多可以达到 `levels` 层，必要时增高头节点和层级生成器。
+    ///
+    /// 已有节点的层级保持不变；如果已经至少有 `levels` 层，则什么也不做。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(4);
+    /// assert_eq!(skiplist.head_level(), 2);
+    /// skiplist.reserve_levels(8);
+    /// assert_eq!(skiplist.head_level(), 8);
+    ///
+    /// skiplist.extend(0..1000);
+    /// assert!(skiplist.max_node_level() > 2);
+    ///
+    /// skiplist.reserve_levels(4);
+    /// assert_eq!(skiplist.head_level(), 8);
+    /// ```
+    pub fn reserve_levels(&mut self, levels: usize) {
+        self.head.grow_head(levels, self.len);
+        if self.level_generator.total() < levels {
+            self.level_generator.set_total(levels);
+        }
+    }
+
+    /// `levels` 层预期可以高效容纳的元素个数，即 `2^levels`，溢出时饱和为 `usize::MAX`。
+    ///
+    /// 每增加一层容量翻倍，而每次查找大约多访问一个节点。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// assert_eq!(SkipList::<i32>::capacity_for_levels(10), 1024);
+    /// assert_eq!(SkipList::<i32>::capacity_for_levels(0), 1);
+    /// assert_eq!(SkipList::<i32>::capacity_for_levels(200), usize::MAX);
+    /// ```
+    #[inline]
+    pub fn capacity_for_levels(levels: usize) -> usize {
+        u32::try_from(levels)
+            .ok()
+            .and_then(|levels| 1usize.checked_shl(levels))
+            .unwrap_or(usize::MAX)
+    }
+
+    /// 尝试增高头节点和层级生成器，使 `capacity()` 至少为 `len() + additional`。
+    ///
+    /// 与 [`Vec::try_reserve`] 类似，分配失败时返回错误而不是中止，此时 skiplist 保持不变。
+    /// 已有节点的层级保持不变；容量已经足够时什么也不做。
+    /// `len() + additional` 溢出时按 `usize::MAX` 计算。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// assert!(skiplist.try_reserve(1000).is_ok());
+    /// assert!(skiplist.capacity() >= 1010);
+    /// assert!(skiplist.iter().copied().eq(0..10));
+    ///
+    /// let level = skiplist.head_level();
+    /// assert!(skiplist.try_reserve(0).is_ok());
+    /// assert_eq!(skiplist.head_level(), level);
+    /// ```
+    pub fn try_reserve(&mut self, additional: usize) -> Result<(), TryReserveError> {
+        let levels = levels_for(self.len.saturating_add(additional));
+        self.head.try_grow_head(levels, self.len)?;
+        if self.level_generator.total() < levels {
+            self.level_generator.set_total(levels);
+        }
+        Ok(())
+    }
+
+    /// 当前层数下预期可以高效容纳的元素个数，即 `2^head_level()`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<i32> = SkipList::with_capacity(1024);
+    /// assert_eq!(skiplist.capacity(), 1024);
+    /// assert_eq!(skiplist.capacity(), SkipList::<i32>::capacity_for_levels(skiplist.head_level()));
+    /// ```
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        Self::capacity_for_levels(self.head_level())
+    }
+
+    /// 把头节点和层级生成器降低到刚好能容纳当前元素的层数，但至少保留 1 层。
+    ///
+    /// 高于新层数的节点的塔会被截断，元素本身保持不变。头节点不高于所需层数时什么也不做。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(1 << 16);
+    /// skiplist.extend(0..100);
+    /// skiplist.shrink_to_fit();
+    /// assert_eq!(skiplist.head_level(), 7);
+    /// assert!(skiplist.capacity() >= skiplist.len());
+    /// assert!(skiplist.iter().copied().eq(0..100));
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.shrink_to(0);
+    }
+
+    /// 与 [`shrink_to_fit`](SkipList::shrink_to_fit) 相同，但保留足以容纳
+    /// `max(len(), min_capacity)` 个元素的层数，`capacity()` 不会低于这个值。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(1 << 16);
+    /// skiplist.extend(0..10);
+    /// skiplist.shrink_to(1000);
+    /// assert_eq!(skiplist.head_level(), 10);
+    /// assert!(skiplist.capacity() >= 1000);
+    ///
+    /// // 只会收缩，不会增长
+    /// skiplist.shrink_to(1 << 20);
+    /// assert_eq!(skiplist.head_level(), 10);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        let levels = levels_for(cmp::max(self.len, min_capacity));
+        if levels < self.head_level() {
+            self.head.shrink_head(levels);
+            self.level_generator.set_total(levels);
+        }
+    }
+
+    /// 开启或关闭自动收缩。开启后，批量移除元素（如 [`retain`](SkipList::retain)、
+    /// [`dedup_by_key`](SkipList::dedup_by_key)）使 `len()` 低于 `capacity() / 4` 时，
+    /// 会自动调用 [`shrink_to_fit`](SkipList::shrink_to_fit)。默认关闭。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity(1024);
+    /// skiplist.extend(0..1000);
+    /// skiplist.set_auto_shrink(true);
+    /// skiplist.retain(|&x| x < 10);
+    /// assert!(skiplist.head_level() < 10);
+    /// assert!(skiplist.iter().copied().eq(0..10));
+    /// ```
+    #[inline]
+    pub fn set_auto_shrink(&mut self, enabled: bool) {
+        self.auto_shrink = enabled;
+    }
+
+    /// 修改层级生成器的晋升概率 `p`，只影响之后插入的节点，已有节点的层级保持不变。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `p` 不在 `(0, 1)` 区间内，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::with_capacity_and_probability(1 << 16, 0.5);
+    /// skiplist.extend(0..2000);
+    /// let before = skiplist.level_histogram();
+    /// // p = 0.5 时大约一半的节点停留在第 0 层
+    /// assert!(before[0] < 1300);
+    ///
+    /// skiplist.set_probability(0.05);
+    /// skiplist.extend(0..2000);
+    /// let after = skiplist.level_histogram();
+    /// // 之后插入的节点几乎都停留在第 0 层
+    /// assert!(after[0] - before[0] > 1700);
+    ///
+    /// let result = std::panic::catch_unwind(move || skiplist.set_probability(f64::NAN));
+    /// let message = result.unwrap_err().downcast::<&str>().unwrap();
+    /// assert_eq!(*message, "p must be in (0, 1).");
+    /// ```
+    #[inline]
+    pub fn set_probability(&mut self, p: f64) {
+        self.level_generator.set_p(p);
+    }
+
+    /// 开启自动收缩且元素过于稀疏时收缩层数。
+    fn shrink_if_sparse(&mut self) {
+        if self.auto_shrink && self.len < self.capacity() / 4 {
+            self.shrink_to_fit();
+        }
+    }
+
+    /// 链表中最高节点的层数，skiplist 为空时返回 0。
+    ///
+    /// 最高的非空层一定从头节点直接链接出去，因此只需检查头节点的链接。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// assert_eq!(skiplist.max_node_level(), 0);
+    /// for i in 0..1000 {
+    ///     skiplist.push_back(i);
+    ///     assert!(skiplist.max_node_level() <= skiplist.head_level());
+    /// }
+    /// assert!(skiplist.max_node_level() >= 1);
+    /// ```
+    pub fn max_node_level(&self) -> usize {
+        self.head
+            .links
+            .iter()
+            .rposition(Option::is_some)
+            .map_or(0, |level| level + 1)
+    }
+
+    /// 统计每一层的节点数量：返回值的第 `n` 项为最高层恰好为 `n` 的节点个数。
+    ///
+    /// 返回的向量长度等于头节点的层数。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..100);
+    /// assert_eq!(skiplist.level_histogram().iter().sum::<usize>(), 100);
+    /// ```
+    pub fn level_histogram(&self) -> Vec<usize> {
+        let mut histogram = vec![0; self.head.level + 1];
+        let mut node = self.head.next_ref();
+        while let Some(current) = node {
+            histogram[current.level] += 1;
+            node = current.next_ref();
+        }
+        histogram
+    }
+
+    /// 创建一个只沿第 `level` 层链接前进的迭代器，依次产生至少达到该层的节点的值。
+    ///
+    /// 第 0 层即为整个链表；`level` 不小于 [`head_level`](SkipList::head_level) 时不产生任何值。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..16).collect();
+    /// assert!(skiplist.level_iter(0).eq(skiplist.iter()));
+    ///
+    /// // 均衡布局下，第 n 层恰好是位置（从 1 开始）为 2^n 的倍数的节点
+    /// skiplist.compact();
+    /// assert!(skiplist.level_iter(2).eq([3, 7, 11, 15].iter()));
+    ///
+    /// let skiplist: SkipList<_> = (0..1000).collect();
+    /// for level in 1..skiplist.head_level() {
+    ///     let lower: Vec<_> = skiplist.level_iter(level - 1).collect();
+    ///     let upper: Vec<_> = skiplist.level_iter(level).collect();
+    ///     assert!(upper.len() <= lower.len());
+    ///     assert!(upper.iter().all(|value| lower.contains(value)));
+    /// }
+    /// assert_eq!(skiplist.level_iter(skiplist.head_level()).count(), 0);
+    /// ```
+    pub fn level_iter(&self, level: usize) -> impl Iterator<Item = &T> + '_ {
+        let mut link = if level <= self.head.level {
+            self.head.links[level]
+        } else {
+            None
+        };
+        iter::from_fn(move || {
+            // SAFETY: 链接指向链表中的有效节点，生命周期受 `&self` 约束
+            let node = unsafe { link?.as_ref() };
+            link = node.links[level];
+            node.item.as_ref()
+        })
+    }
+
+    /// 沿第 `level` 层的链接累加所有跨度，用于检查跨度记录是否正确。
+    ///
+    /// 对结构正确的 skiplist，每一层的结果都等于 [`len`](SkipList::len)；
+    /// `level` 不小于 [`head_level`](SkipList::head_level) 时返回 0。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..100).collect();
+    /// for i in 0..50 {
+    ///     skiplist.insert(i, (i * 7) % skiplist.len());
+    ///     skiplist.remove((i * 13) % skiplist.len());
+    /// }
+    /// skiplist.retain(|&x| x % 3 != 0);
+    /// for level in 0..skiplist.head_level() {
+    ///     assert_eq!(skiplist.total_span(level), skiplist.len());
+    /// }
+    /// ```
+    pub fn total_span(&self, level: usize) -> usize {
+        if level > self.head.level {
+            return 0;
+        }
+        let mut total = 0;
+        let mut node: &SkipNode<T> = &self.head;
+        loop {
+            total += node.links_len[level];
+            match node.links[level] {
+                // SAFETY: 链接指向链表中的有效节点，生命周期受 `&self` 约束
+                Some(next) => node = unsafe { next.as_ref() },
+                None => break,
+            }
+        }
+        total
+    }
+
+    /// 根据当前各层的节点数量，估计一次按索引查找平均需要比较的节点个数。
+    ///
+    /// 设至少达到第 `l` 层的节点有 `c(l)` 个，则第 `l` 层的每一段（由更高一层的相邻节点划分，
+    /// 头节点也算作一个起点）平均有 `c(l) / (c(l+1) + 1)` 个节点，下降时平均要走过其中一半，
+    /// 并在停下时再比较一次。估计值是所有非空层上这些代价之和；空的 skiplist 返回 0。
+    ///
+    /// 估计直接使用实际的层级分布，而不是层级生成器的晋升概率，因此可以用来判断是否值得调用
+    /// [`compact`](SkipList::compact)。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let values: Vec<_> = (0..1024).collect();
+    /// let balanced = SkipList::from_sorted_slice(&values);
+    ///
+    /// // 只有一层时，所有节点都挤在第 0 层
+    /// let mut degenerate = SkipList::with_capacity(1);
+    /// degenerate.extend(0..1024);
+    ///
+    /// assert!(balanced.expected_search_cost() < degenerate.expected_search_cost());
+    /// assert!(balanced.expected_search_cost() < 25.0);
+    /// assert_eq!(SkipList::<i32>::new().expected_search_cost(), 0.0);
+    /// ```
+    pub fn expected_search_cost(&self) -> f64 {
+        let histogram = self.level_histogram();
+        let mut cost = 0.0;
+        // 从最高层向下累加，`above` 为至少达到上一层的节点个数
+        let mut above = 0;
+        for &count in histogram.iter().rev() {
+            let at_least = above + count;
+            if at_least > 0 {
+                let segment = at_least as f64 / (above + 1) as f64;
+                cost += segment / 2.0 + 1.0;
+            }
+            above = at_least;
+        }
+        cost
+    }
+
+    /// skiplist 是否以 `prefix` 开头，空的 `prefix` 总是返回 `true`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (1..5).collect();
+    /// assert!(skiplist.starts_with(&[1, 2]));
+    /// assert!(!skiplist.starts_with(&[2, 3]));
+    /// assert!(skiplist.starts_with(&[]));
+    /// ```
+    pub fn starts_with(&self, prefix: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        prefix.len() <= self.len && self.iter().zip(prefix).all(|(a, b)| a == b)
+    }
+
+    /// skiplist 是否以 `suffix` 结尾，空的 `suffix` 总是返回 `true`。
+    ///
+    /// 从最后一个节点开始沿 `prev` 反向比较。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (1..5).collect();
+    /// assert!(skiplist.ends_with(&[3, 4]));
+    /// assert!(!skiplist.ends_with(&[2, 3]));
+    /// assert!(skiplist.ends_with(&[]));
+    /// ```
+    pub fn ends_with(&self, suffix: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        suffix.len() <= self.len
+            && self
+                .iter()
+                .rev()
+                .zip(suffix.iter().rev())
+                .all(|(a, b)| a == b)
+    }
+
+    /// 查找 `needle` 作为连续子序列第一次出现的起始索引，空的 `needle` 返回 `Some(0)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.contains_subsequence(&[3, 4]), Some(3));
+    /// assert_eq!(skiplist.contains_subsequence(&[4, 3]), None);
+    /// assert_eq!(skiplist.contains_subsequence(&[9, 10]), None);
+    /// ```
+    pub fn contains_subsequence(&self, needle: &[T]) -> Option<usize>
+    where
+        T: PartialEq,
+    {
+        if needle.len() > self.len {
+            return None;
+        }
+        let mut iter = self.iter();
+        for start in 0..=self.len - needle.len() {
+            if iter.clone().zip(needle).all(|(a, b)| a == b) {
+                return Some(start);
+            }
+            iter.next();
+        }
+        None
+    }
+
+    /// 从尾部开始沿 `prev` 反向查找，返回最后一个满足 `p` 的元素的索引。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.rposition(|x| x % 2 == 0), Some(8));
+    /// assert_eq!(skiplist.rposition(|&x| x > 10), None);
+    /// ```
+    pub fn rposition<P>(&self, p: P) -> Option<usize>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.iter().rposition(p)
+    }
+
+    /// 按顺序累加每个元素的权重 `weight`，返回累计权重第一次超过 `target` 的元素的索引。
+    ///
+    /// 在 `[0, 总权重)` 中均匀选取 `target`，即可按权重随机抽样。
+    /// 所有权重之和不超过 `target` 时返回 `None`。
+    /// 节点上不保存权重，因此需要遍历，时间为 `O(n)`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<usize> = [1, 2, 3].into_iter().collect();
+    /// assert_eq!(skiplist.find_by_cumulative(0, |&w| w), Some(0));
+    /// assert_eq!(skiplist.find_by_cumulative(2, |&w| w), Some(1));
+    /// assert_eq!(skiplist.find_by_cumulative(3, |&w| w), Some(2));
+    /// assert_eq!(skiplist.find_by_cumulative(6, |&w| w), None);
+    /// ```
+    pub fn find_by_cumulative<F>(&self, target: usize, weight: F) -> Option<usize>
+    where
+        F: Fn(&T) -> usize,
+    {
+        let mut total = 0usize;
+        self.iter().position(|value| {
+            total = total.saturating_add(weight(value));
+            total > target
+        })
+    }
+
+    /// 与切片逐个比较元素，长度和每个元素都相等时返回 `true`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (1..4).collect();
+    /// assert!(skiplist.eq_slice(&[1, 2, 3]));
+    /// assert!(!skiplist.eq_slice(&[1, 2]));
+    /// assert!(skiplist == [1, 2, 3][..]);
+    /// ```
+    pub fn eq_slice(&self, other: &[T]) -> bool
+    where
+        T: PartialEq,
+    {
+        self.len == other.len() && self.iter().eq(other)
+    }
+
+    /// 是否存在相等的相邻元素，只遍历一次。
+    ///
+    /// 对排好序的 skiplist，这等价于是否存在重复元素；未排序时不相邻的重复元素不会被发现。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 2, 2, 3].into_iter().collect();
+    /// assert!(skiplist.has_adjacent_duplicates());
+    /// let skiplist: SkipList<_> = [1, 2, 3].into_iter().collect();
+    /// assert!(!skiplist.has_adjacent_duplicates());
+    ///
+    /// // 两个 1 不相邻
+    /// let skiplist: SkipList<_> = [1, 2, 1].into_iter().collect();
+    /// assert!(!skiplist.has_adjacent_duplicates());
+    /// ```
+    pub fn has_adjacent_duplicates(&self) -> bool
+    where
+        T: PartialEq,
+    {
+        let mut iter = self.iter();
+        let Some(mut prev) = iter.next() else {
+            return false;
+        };
+        for item in iter {
+            if item == prev {
+                return true;
+            }
+            prev = item;
+        }
+        false
+    }
+
+    /// 把键相同的相邻元素分为一组，按顺序返回所有分组，只遍历一次。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = [1, 1, 2, 2, 2, 3].into_iter().collect();
+    /// let groups = skiplist.group_by(|&x| x);
+    /// let sizes: Vec<_> = groups.iter().map(Vec::len).collect();
+    /// assert_eq!(sizes, [2, 3, 1]);
+    /// assert_eq!(groups[1], [&2, &2, &2]);
+    /// ```
+    pub fn group_by<K, F>(&self, mut key: F) -> Vec<Vec<&T>>
+    where
+        K: PartialEq,
+        F: FnMut(&T) -> K,
+    {
+        let mut groups: Vec<Vec<&T>> = Vec::new();
+        let mut last_key = None;
+        for item in self.iter() {
+            let k = key(item);
+            match groups.last_mut() {
+                Some(group) if last_key.as_ref() == Some(&k) => group.push(item),
+                _ => groups.push(vec![item]),
+            }
+            last_key = Some(k);
+        }
+        groups
+    }
+
+    /// 创建一个按顺序遍历 skiplist 的迭代器。
+    ///
+    /// 迭代器的 `nth` 借助高层链接跳过元素，耗时 O(log n)，而不是逐个前进；
+    /// `count` 直接返回剩余的元素个数，`last` 直接跳到末尾的节点，都不会遍历。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..10);
+    /// for (i, value) in skiplist.iter().enumerate() {
+    ///     assert_eq!(i, *value);
+    /// }
+    ///
+    /// let skiplist: SkipList<_> = (0..1000).collect();
+    /// assert_eq!(skiplist.iter().nth(500), skiplist.get(500));
+    /// let mut iter = skiplist.iter();
+    /// iter.next();
+    /// assert_eq!(iter.nth(300), Some(&301));
+    /// assert_eq!(iter.len(), 698);
+    /// assert_eq!(iter.nth(698), None);
+    ///
+    /// let mut iter = skiplist.iter();
+    /// iter.nth(9);
+    /// assert_eq!(iter.count(), 990);
+    /// assert_eq!(skiplist.iter().count(), skiplist.len());
+    /// assert_eq!(skiplist.iter().last(), skiplist.get(skiplist.len() - 1));
+    /// ```
+    #[inline]
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter::from_head(&self.head, self.len)
+    }
+
+    /// 创建一个同时给出索引和值的迭代器，等价于 `iter().enumerate()`。
+    ///
+    /// 每沿第 0 层前进一步索引加 1；反向遍历时索引仍然与位置对应。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (10..20).collect();
+    /// let mut expected = 0;
+    /// for (i, value) in skiplist.iter_enumerate() {
+    ///     assert_eq!(i, expected);
+    ///     assert_eq!(*value, i + 10);
+    ///     expected += 1;
+    /// }
+    /// assert_eq!(expected, skiplist.len());
+    ///
+    /// assert_eq!(skiplist.iter_enumerate().next_back(), Some((9, &19)));
+    /// assert_eq!(SkipList::<i32>::new().iter_enumerate().next(), None);
+    /// ```
+    #[inline]
+    pub fn iter_enumerate(&self) -> iter::Enumerate<Iter<'_, T>> {
+        self.iter().enumerate()
+    }
+
+    /// 创建一个按顺序遍历 skiplist 的可变迭代器，也可以从末尾反向遍历。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist = SkipList::new();
+    /// skiplist.extend(0..10);
+    /// for value in skiplist.iter_mut() {
+    ///     *value *= 2;
+    /// }
+    /// assert_eq!(skiplist.get(3), Some(&6));
+    ///
+    /// let mut skiplist: SkipList<i32> = (1..6).collect();
+    /// let mut order = Vec::new();
+    /// for value in skiplist.iter_mut().rev() {
+    ///     order.push(*value);
+    ///     *value = -*value;
+    /// }
+    /// assert_eq!(order, [5, 4, 3, 2, 1]);
+    /// assert!(skiplist.iter().eq([-1, -2, -3, -4, -5].iter()));
+    ///
+    /// // 两端交替前进，在中间相遇时结束
+    /// let mut iter = skiplist.iter_mut();
+    /// let (a, b) = (iter.next().unwrap(), iter.next_back().unwrap());
+    /// std::mem::swap(a, b);
+    /// assert_eq!(iter.len(), 3);
+    /// assert_eq!(iter.rev().count(), 3);
+    /// assert!(skiplist.iter().eq([-5, -2, -3, -4, -1].iter()));
+    /// ```
+    #[inline]
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut::from_head(&mut self.head, self.len)
+    }
+
+    /// 创建一个可变迭代器，遍历索引位于 `[start, end)` 内的元素。
+    ///
+    /// 只需一次下降找到 `start` 处的节点，之后沿第 0 层前进。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `start > end` 或 `end` 大于 skiplist 的长度，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// for value in skiplist.range_mut(2, 7) {
+    ///     *value *= 2;
+    /// }
+    /// assert!(skiplist.iter().eq([0, 1, 4, 6, 8, 10, 12, 7, 8, 9].iter()));
+    /// ```
+    pub fn range_mut(&mut self, start: usize, end: usize) -> IterMut<'_, T> {
+        if start > end || end > self.len {
+            range_out_of_bounds(start, end, self.len);
+        }
+        IterMut::from_range(&mut self.head, start, end)
+    }
+
+    /// 把索引位于 `[start, end)` 内的元素克隆到一个 `Vec` 中，只需一次下降找到 `start`。
+    ///
+    /// `end` 超过长度时按长度截断；`start` 不小于截断后的 `end` 时返回空的 `Vec`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// assert_eq!(skiplist.range_cloned(2, 5), [2, 3, 4]);
+    /// assert_eq!(skiplist.range_cloned(8, 100), [8, 9]);
+    /// assert!(skiplist.range_cloned(12, 20).is_empty());
+    /// ```
+    pub fn range_cloned(&self, start: usize, end: usize) -> Vec<T>
+    where
+        T: Clone,
+    {
+        let end = cmp::min(end, self.len);
+        if start >= end {
+            return Vec::new();
+        }
+        Iter::from_range(&self.head, start, end).cloned().collect()
+    }
+
+    /// 创建一个从第一个元素开始、每隔 `step` 个元素产生一个引用的迭代器。
+    ///
+    /// 每一步都借助高层链接跳过中间的元素，而不是逐个前进。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `step` 为 0，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..300).collect();
+    /// let stepped = skiplist.iter_step(10);
+    /// assert_eq!(stepped.len(), 30);
+    /// assert!(stepped.copied().eq((0..300).step_by(10)));
+    ///
+    /// let skiplist: SkipList<_> = (0..7).collect();
+    /// assert!(skiplist.iter_step(3).eq([0, 3, 6].iter()));
+    /// ```
+    pub fn iter_step(&self, step: usize) -> StepIter<'_, T> {
+        if step == 0 {
+            panic!("Step must be non-zero.");
+        }
+        StepIter {
+            iter: self.iter(),
+            step,
+            first: true,
+        }
+    }
+
+    /// 创建一个迭代器，每次产生 `size` 个连续元素的引用，丢弃末尾不足 `size` 个的部分。
+    ///
+    /// 剩余的元素可以通过 [`ChunksExact::remainder`] 取得。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `size` 为 0，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let skiplist: SkipList<_> = (0..10).collect();
+    /// let mut chunks = skiplist.chunks_exact(4);
+    /// assert_eq!(chunks.next(), Some(vec![&0, &1, &2, &3]));
+    /// assert_eq!(chunks.next(), Some(vec![&4, &5, &6, &7]));
+    /// assert_eq!(chunks.next(), None);
+    /// assert_eq!(chunks.remainder().collect::<Vec<_>>(), vec![&8, &9]);
+    /// ```
+    pub fn chunks_exact(&self, size: usize) -> ChunksExact<'_, T> {
+        if size == 0 {
+            panic!("Chunk size must be non-zero.");
+        }
+        let end = self.len - self.len % size;
+        ChunksExact {
+            iter: Iter::from_range(&self.head, 0, end),
+            remainder: Iter::from_range(&self.head, end, self.len),
+            size,
+        }
+    }
+
+    /// 创建一个迭代器，每次产生最多 `size` 个连续元素的可变引用，最后一块可能不足 `size` 个。
+    ///
+    /// 每个元素只会出现在一个块中，因此所有块中的引用互不重叠。
+    ///
+    /// # Panics
+    ///
+    /// 如果 `size` 为 0，则会恐慌。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// for chunk in skiplist.chunks_mut(3) {
+    ///     for value in chunk {
+    ///         *value += 1;
+    ///     }
+    /// }
+    /// assert!(skiplist.iter().copied().eq(1..11));
+    /// assert_eq!(skiplist.chunks_mut(3).map(|chunk| chunk.len()).collect::<Vec<_>>(), [3, 3, 3, 1]);
+    /// ```
+    pub fn chunks_mut(&mut self, size: usize) -> ChunksMut<'_, T> {
+        if size == 0 {
+            panic!("Chunk size must be non-zero.");
+        }
+        ChunksMut {
+            iter: self.iter_mut(),
+            size,
+        }
+    }
+
+    /// 创建一个指向第一个元素的光标；skiplist 为空时光标指向“幽灵”位置。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..3).collect();
+    /// let mut cursor = skiplist.cursor_front();
+    /// assert_eq!(cursor.current(), Some(&mut 0));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.index(), Some(1));
+    /// ```
+    pub fn cursor_front(&mut self) -> CursorMut<'_, T> {
+        let pos = if self.is_empty() { 0 } else { 1 };
+        let current = self.head.advance_link(pos).unwrap();
+        CursorMut {
+            current,
+            pos,
+            list: self,
+        }
+    }
+
+    /// 创建一个直接指向索引 `index` 处元素的光标，通过逐层下降定位，不需要从头逐个移动。
+    ///
+    /// 索引越界时返回 `None`。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..10).collect();
+    /// let mut cursor = skiplist.cursor_at(5).unwrap();
+    /// assert_eq!(cursor.current(), Some(&mut 5));
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.index(), Some(4));
+    /// assert!(skiplist.cursor_at(10).is_none());
+    ///
+    /// // 拼接之后光标仍然指向同一个元素
+    /// let mut cursor = skiplist.cursor_at(3).unwrap();
+    /// cursor.splice_after((10..13).collect());
+    /// assert_eq!(cursor.current(), Some(&mut 3));
+    /// assert_eq!(cursor.peek_prev(), Some(&2));
+    /// cursor.move_next();
+    /// assert_eq!(cursor.current(), Some(&mut 10));
+    /// assert!(skiplist.iter().eq([0, 1, 2, 3, 10, 11, 12, 4, 5, 6, 7, 8, 9].iter()));
+    /// ```
+    pub fn cursor_at(&mut self, index: usize) -> Option<CursorMut<'_, T>> {
+        if index >= self.len {
+            return None;
+        }
+        // 使用链表中保存的链接，而不是新创建的可变借用，见 `SkipNode::advance_link`
+        let current = self.head.advance_link(index + 1)?;
+        Some(CursorMut {
+            current,
+            pos: index + 1,
+            list: self,
+        })
+    }
+
+    /// 为 `capacity` 个元素选择层数，并把 `iter` 中的元素按均衡布局依次追加到末尾。
+    fn from_balanced<I>(capacity: usize, iter: I) -> Self
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut skiplist = SkipList::with_capacity(capacity);
+        let top = skiplist.head.level;
+        let mut tails = skiplist.head.tails(0);
+        for value in iter {
+            let pos = skiplist.len + 1;
+            let node = Box::new(SkipNode::new(value, balanced_level(pos, top)));
+            SkipNode::push_tail(&mut tails, node);
+            skiplist.len += 1;
+        }
+        skiplist
+    }
+
+    /// 把 `iter` 中的元素依次连成一条独立的链，返回它的头节点和元素个数。
+    ///
+    /// 新节点的层级取自自身的层级生成器，头节点与自身的头节点等高，
+    /// 因此这条链可以直接拼接到自身的头部或末尾。
+    fn detached_chain<I>(&mut self, iter: I) -> (Box<SkipNode<T>>, usize)
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let mut head = Box::new(SkipNode::head(self.head.level + 1));
+        let mut tails = head.tails(0);
+        let mut len = 0;
+        for value in iter {
+            let new_node = Box::new(SkipNode::new(value, self.level_generator.random()));
+            SkipNode::push_tail(&mut tails, new_node);
+            len += 1;
+        }
+        (head, len)
+    }
+
+    /// 创建一个与自身层数和层级生成器相同的空 skiplist。
+    fn empty_like(&self) -> Self {
+        SkipList {
+            head: Box::new(SkipNode::head(self.head.level + 1)),
+            len: 0,
+            level_generator: self.level_generator.clone(),
+            auto_shrink: self.auto_shrink,
+        }
+    }
 
     /// Gets a pointer to the node with the given index.
     fn get_index(&self, index: usize) -> Option<&SkipNode<T>> {
@@ -220,4 +2957,699 @@ impl<T> SkipList<T> {
             self.head.advance_mut(index + 1)
         }
     }
-}
\ No newline at end of file
+}
+
+/// 索引越界时的统一恐慌信息。
+#[cold]
+fn index_out_of_bounds(index: usize, len: usize) -> ! {
+    panic!(
+        "index {} out of bounds for skiplist of length {}",
+        index, len
+    );
+}
+
+/// 区间越界时的统一恐慌信息。
+#[cold]
+fn range_out_of_bounds(start: usize, end: usize, len: usize) -> ! {
+    panic!(
+        "range {}..{} out of bounds for skiplist of length {}",
+        start, end, len
+    );
+}
+
+/// 容纳 `capacity` 个元素所需的最少层数，即 `ceil(log2(capacity))`，至少为 1。
+fn levels_for(capacity: usize) -> usize {
+    let levels = capacity
+        .checked_next_power_of_two()
+        .map_or(usize::BITS, usize::trailing_zeros);
+    cmp::max(levels as usize, 1)
+}
+
+/// 均衡布局中位置 `pos`（从 1 开始）的节点层级，不超过 `top`。
+fn balanced_level(pos: usize, top: usize) -> usize {
+    cmp::min(pos.trailing_zeros() as usize, top)
+}
+
+impl<T> SkipList<SkipList<T>> {
+    /// 按顺序把所有内层 skiplist 拼接成一个 skiplist。
+    ///
+    /// 每个内层链表都通过 [`append`](SkipList::append) 整体拼接，不会逐个移动元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let nested: SkipList<SkipList<_>> = [0..2, 2..5, 5..6]
+    ///     .into_iter()
+    ///     .map(|range| range.collect())
+    ///     .collect();
+    /// let flat = nested.flatten();
+    /// assert_eq!(flat.len(), 6);
+    /// assert!(flat.iter().copied().eq(0..6));
+    /// ```
+    pub fn flatten(self) -> SkipList<T> {
+        let mut flat = SkipList::new();
+        for mut inner in self {
+            flat.append(&mut inner);
+        }
+        flat
+    }
+}
+
+/// [`SkipList::extract_if`] 返回的迭代器。
+pub struct ExtractIf<'a, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    pass: RetainPass<'a, T>,
+    pred: F,
+    // `pred` 恐慌后不再在析构时继续调用它
+    panicked: bool,
+}
+
+impl<T, F> Iterator for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(item) = self.pass.peek_next() {
+            self.panicked = true;
+            let extract = (self.pred)(item);
+            self.panicked = false;
+            if extract {
+                return self.pass.remove_next();
+            }
+            self.pass.keep_next();
+        }
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.pass.remaining()))
+    }
+}
+
+impl<T, F> Drop for ExtractIf<'_, T, F>
+where
+    F: FnMut(&mut T) -> bool,
+{
+    fn drop(&mut self) {
+        // 即使 `pred` 在这里恐慌，`pass` 析构时也会保留剩余节点
+        if !self.panicked {
+            self.for_each(drop);
+        }
+    }
+}
+
+/// [`SkipList::drain_all`] 返回的迭代器。
+pub struct Drain<'a, T> {
+    iter: IntoIter<T>,
+    _marker: PhantomData<&'a mut SkipList<T>>,
+}
+
+impl<T> Iterator for Drain<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Drain<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.iter.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Drain<'_, T> {}
+
+/// [`SkipList::splice`] 返回的迭代器。
+///
+/// 被丢弃时把替换的元素和区间之后的元素依次拼接回 skiplist。
+pub struct Splice<'a, T> {
+    list: &'a mut SkipList<T>,
+    removed: IntoIter<T>,
+    replacement: SkipList<T>,
+    tail: SkipList<T>,
+}
+
+impl<T> Iterator for Splice<'_, T> {
+    type Item = T;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.removed.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.removed.size_hint()
+    }
+}
+
+impl<T> DoubleEndedIterator for Splice<'_, T> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.removed.next_back()
+    }
+}
+
+impl<T> ExactSizeIterator for Splice<'_, T> {}
+
+impl<T> Drop for Splice<'_, T> {
+    fn drop(&mut self) {
+        self.list.append(&mut self.replacement);
+        self.list.append(&mut self.tail);
+    }
+}
+
+/// [`SkipList::iter_step`] 返回的迭代器。
+pub struct StepIter<'a, T> {
+    iter: Iter<'a, T>,
+    step: usize,
+    // 第一个元素不需要跳过任何元素
+    first: bool,
+}
+
+impl<'a, T> Iterator for StepIter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if mem::take(&mut self.first) {
+            self.iter.next()
+        } else {
+            // `Iter::nth` 沿着塔结构跳过中间的元素
+            self.iter.nth(self.step - 1)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.iter.len();
+        let len = if !self.first {
+            remaining / self.step
+        } else if remaining == 0 {
+            0
+        } else {
+            1 + (remaining - 1) / self.step
+        };
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for StepIter<'_, T> {}
+
+/// [`SkipList::chunks_exact`] 返回的迭代器。
+pub struct ChunksExact<'a, T> {
+    iter: Iter<'a, T>,
+    remainder: Iter<'a, T>,
+    size: usize,
+}
+
+impl<'a, T> ChunksExact<'a, T> {
+    /// 末尾不足一个完整块的元素。
+    pub fn remainder(&self) -> Iter<'a, T> {
+        self.remainder.clone()
+    }
+}
+
+impl<'a, T> Iterator for ChunksExact<'a, T> {
+    type Item = Vec<&'a T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.iter.len() == 0 {
+            return None;
+        }
+        Some(self.iter.by_ref().take(self.size).collect())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = self.iter.len() / self.size;
+        (chunks, Some(chunks))
+    }
+}
+
+impl<T> ExactSizeIterator for ChunksExact<'_, T> {}
+
+/// [`SkipList::chunks_mut`] 返回的迭代器。
+pub struct ChunksMut<'a, T> {
+    iter: IterMut<'a, T>,
+    size: usize,
+}
+
+impl<'a, T> Iterator for ChunksMut<'a, T> {
+    type Item = Vec<&'a mut T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.iter.len() == 0 {
+            return None;
+        }
+        Some(self.iter.by_ref().take(self.size).collect())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let chunks = self.iter.len().div_ceil(self.size);
+        (chunks, Some(chunks))
+    }
+}
+
+impl<T> ExactSizeIterator for ChunksMut<'_, T> {}
+
+/// 可以在 skiplist 中前后移动并修改元素的光标。
+///
+/// 除了各个元素之外，光标还可能指向一个不对应任何元素的“幽灵”位置，
+/// 它位于最后一个元素之后、第一个元素之前。
+pub struct CursorMut<'a, T> {
+    list: &'a mut SkipList<T>,
+    // 当前节点，幽灵位置对应头节点
+    current: NonNull<SkipNode<T>>,
+    // 当前节点的位置，头节点为 0
+    pos: usize,
+}
+
+impl<T> CursorMut<'_, T> {
+    /// 当前元素的索引，位于幽灵位置时返回 `None`。
+    pub fn index(&self) -> Option<usize> {
+        self.pos.checked_sub(1)
+    }
+
+    /// 当前元素的可变引用，位于幽灵位置时返回 `None`。
+    pub fn current(&mut self) -> Option<&mut T> {
+        // SAFETY: 光标独占 skiplist，`current` 总是链表中的有效节点
+        unsafe { self.current.as_mut() }.item.as_mut()
+    }
+
+    /// 移动到下一个元素；位于最后一个元素时移动到幽灵位置，位于幽灵位置时移动到第一个元素。
+    pub fn move_next(&mut self) {
+        // SAFETY: 同 `current`
+        match unsafe { self.current.as_ref() }.links[0] {
+            Some(next) => {
+                self.current = next;
+                self.pos += 1;
+            }
+            None => {
+                self.current = NonNull::from(&mut *self.list.head);
+                self.pos = 0;
+            }
+        }
+    }
+
+    /// 移动到上一个元素；位于第一个元素时移动到幽灵位置，位于幽灵位置时移动到最后一个元素。
+    pub fn move_prev(&mut self) {
+        if self.pos == 0 {
+            let len = self.list.len;
+            self.current = self.list.head.advance_link(len).unwrap();
+            self.pos = len;
+        } else if self.pos == 1 {
+            // 第一个节点不链接回头节点
+            self.current = NonNull::from(&mut *self.list.head);
+            self.pos = 0;
+        } else {
+            // SAFETY: 同 `current`，第一个节点之后的节点的 `prev` 总是存在
+            self.current = unsafe { self.current.as_ref() }.prev.unwrap();
+            self.pos -= 1;
+        }
+    }
+
+    /// 把 `other` 的所有元素插入到当前元素之后，光标位置不变；
+    /// 位于幽灵位置时插入到 skiplist 的头部。
+    ///
+    /// 插入通过拆分和拼接各层链接完成，不会逐个重新插入元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let other: SkipList<_> = (10..13).collect();
+    /// let mut cursor = skiplist.cursor_front();
+    /// cursor.move_next();
+    /// cursor.splice_after(other);
+    /// assert_eq!(cursor.current(), Some(&mut 1));
+    /// assert_eq!(cursor.peek_next(), Some(&10));
+    /// assert!(skiplist.iter().eq([0, 1, 10, 11, 12, 2, 3, 4].iter()));
+    /// ```
+    ///
+    /// 拼接之后光标仍然可以读写当前节点并继续移动：
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let mut cursor = skiplist.cursor_front();
+    /// cursor.splice_after((10..13).collect());
+    /// assert_eq!(cursor.current(), Some(&mut 0));
+    /// assert_eq!(cursor.peek_prev(), None);
+    /// *cursor.current().unwrap() = 100;
+    /// cursor.move_next();
+    /// assert_eq!(cursor.peek_prev(), Some(&100));
+    ///
+    /// // 位于幽灵位置时插入到头部
+    /// cursor.move_prev();
+    /// cursor.move_prev();
+    /// cursor.splice_after((20..22).collect());
+    /// assert_eq!(cursor.current(), None);
+    /// assert_eq!(cursor.peek_prev(), Some(&4));
+    /// assert_eq!(cursor.peek_next(), Some(&20));
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.current(), Some(&mut 4));
+    /// assert!(skiplist.iter().eq([20, 21, 100, 10, 11, 12, 1, 2, 3, 4].iter()));
+    /// ```
+    pub fn splice_after(&mut self, mut other: SkipList<T>) {
+        let mut tail = self.list.split_off(self.pos);
+        self.list.append(&mut other);
+        self.list.append(&mut tail);
+        // 拆分与拼接会经由其他链接修改节点，也可能换用更高的头节点，
+        // 因此沿链接重新取得当前节点
+        self.current = self.list.head.advance_link(self.pos).unwrap();
+    }
+
+    /// 查看下一个元素而不移动光标；位于幽灵位置时返回第一个元素。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let mut cursor = skiplist.cursor_front();
+    /// cursor.move_next();
+    /// cursor.move_next();
+    /// assert_eq!(cursor.peek_next(), Some(&3));
+    /// assert_eq!(cursor.peek_prev(), Some(&1));
+    /// assert_eq!(cursor.current(), Some(&mut 2));
+    ///
+    /// cursor.move_prev();
+    /// cursor.move_prev();
+    /// assert_eq!(cursor.peek_prev(), None);
+    /// ```
+    pub fn peek_next(&self) -> Option<&T> {
+        // SAFETY: 同 `current`
+        let current = unsafe { self.current.as_ref() };
+        current.next_ref().and_then(|next| next.item.as_ref())
+    }
+
+    /// 查看上一个元素而不移动光标；位于幽灵位置时返回最后一个元素。
+    pub fn peek_prev(&self) -> Option<&T> {
+        if self.pos == 0 {
+            return self.list.head.advance(self.list.len)?.item.as_ref();
+        }
+        // SAFETY: 同 `current`
+        let current = unsafe { self.current.as_ref() };
+        current.prev_ref().and_then(|prev| prev.item.as_ref())
+    }
+}
+
+// trait 实现
+
+impl<T> default::Default for SkipList<T> {
+    fn default() -> SkipList<T> {
+        SkipList::new()
+    }
+}
+
+impl<T: Clone> Clone for SkipList<T> {
+    // 逐个复制节点，保持与原链表相同的层级结构
+    fn clone(&self) -> Self {
+        let mut head = Box::new(SkipNode::head(self.head.level + 1));
+        let mut tails = head.tails(0);
+        let mut node = self.head.next_ref();
+        while let Some(current) = node {
+            let item = current.item.clone().unwrap();
+            SkipNode::push_tail(&mut tails, Box::new(SkipNode::new(item, current.level)));
+            node = current.next_ref();
+        }
+        SkipList {
+            head,
+            len: self.len,
+            level_generator: self.level_generator.clone(),
+            auto_shrink: self.auto_shrink,
+        }
+    }
+
+    /// 尽量复用已有节点：公共长度内的元素原地覆盖，多余的节点被释放，不足的部分追加到末尾。
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    ///
+    /// let source: SkipList<_> = (10..15).collect();
+    /// let mut skiplist: SkipList<_> = (0..5).collect();
+    /// let before: Vec<*const i32> = skiplist.iter().map(|x| x as *const _).collect();
+    ///
+    /// skiplist.clone_from(&source);
+    /// assert!(skiplist.iter().eq(source.iter()));
+    ///
+    /// // 长度相同时不会分配新的节点
+    /// let after: Vec<*const i32> = skiplist.iter().map(|x| x as *const _).collect();
+    /// assert_eq!(before, after);
+    ///
+    /// skiplist.clone_from(&(0..8).collect());
+    /// assert!(skiplist.iter().copied().eq(0..8));
+    /// skiplist.clone_from(&(0..2).collect());
+    /// assert!(skiplist.iter().copied().eq(0..2));
+    /// ```
+    ///
+    /// 复制元素时 panic，已经追加的节点仍然计入长度：
+    ///
+    /// ```
+    /// use skiplist::SkipList;
+    /// use std::panic::{self, AssertUnwindSafe};
+    ///
+    /// #[derive(Debug, PartialEq)]
+    /// struct Fragile(i32);
+    ///
+    /// impl Clone for Fragile {
+    ///     fn clone(&self) -> Self {
+    ///         if self.0 == 3 {
+    ///             panic!("boom");
+    ///         }
+    ///         Fragile(self.0)
+    ///     }
+    /// }
+    ///
+    /// let source: SkipList<_> = (0..5).map(Fragile).collect();
+    /// let mut skiplist: SkipList<_> = (0..1).map(Fragile).collect();
+    ///
+    /// let result = panic::catch_unwind(AssertUnwindSafe(|| skiplist.clone_from(&source)));
+    /// assert!(result.is_err());
+    /// assert_eq!(skiplist.len(), 3);
+    ///
+    /// skiplist.push_back(Fragile(9));
+    /// skiplist.append(&mut (10..12).map(Fragile).collect());
+    /// assert_eq!(skiplist.len(), 6);
+    /// assert!(skiplist.iter().map(|x| x.0).eq([0, 1, 2, 9, 10, 11]));
+    /// ```
+    fn clone_from(&mut self, source: &Self) {
+        if self.len > source.len {
+            drop(self.split_off(source.len));
+        }
+        for (dst, value) in self.iter_mut().zip(source.iter()) {
+            dst.clone_from(value);
+        }
+        if self.len < source.len {
+            let mut tails = self.head.tails(self.len);
+            // 每链接一个节点就更新长度，`clone` 中途 panic 时长度仍与链表一致
+            for value in source.iter().skip(self.len) {
+                let new_node =
+                    Box::new(SkipNode::new(value.clone(), self.level_generator.random()));
+                SkipNode::push_tail(&mut tails, new_node);
+                self.len += 1;
+            }
+        }
+    }
+}
+
+/// 以 `[e0, e1, ...]` 的形式显示所有元素。
+///
+/// 指定精度时只显示前若干个元素，其余部分以 `...` 省略，便于在日志中输出很长的 skiplist。
+///
+/// # Examples
+///
+/// ```
+/// use skiplist::SkipList;
+///
+/// let skiplist: SkipList<_> = (0..100).collect();
+/// assert_eq!(format!("{:.3}", skiplist), "[0, 1, 2, ...]");
+///
+/// let short: SkipList<_> = (0..3).collect();
+/// assert_eq!(format!("{}", short), "[0, 1, 2]");
+/// assert_eq!(format!("{:.5}", short), "[0, 1, 2]");
+/// ```
+impl<T: fmt::Display> fmt::Display for SkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let limit = f.precision().unwrap_or(self.len);
+        write!(f, "[")?;
+        for (i, entry) in self.iter().take(limit).enumerate() {
+            if i != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", entry)?;
+        }
+        if self.len > limit {
+            if limit != 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "...")?;
+        }
+        write!(f, "]")
+    }
+}
+
+/// 以 `SkipList [e0, e1, ...]` 的形式只显示元素的值，不包含节点的指针。
+///
+/// # Examples
+///
+/// ```
+/// use skiplist::SkipList;
+///
+/// let skiplist: SkipList<_> = ["a", "b"].into_iter().collect();
+/// assert_eq!(format!("{:?}", skiplist), r#"SkipList ["a", "b"]"#);
+/// assert_eq!(format!("{:?}", SkipList::<i32>::new()), "SkipList []");
+/// ```
+impl<T: fmt::Debug> fmt::Debug for SkipList<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SkipList ")?;
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// 按索引访问元素。
+///
+/// # Panics
+///
+/// 如果索引越界，则会恐慌。
+///
+/// # Examples
+///
+/// ```
+/// use skiplist::SkipList;
+///
+/// let mut skiplist: SkipList<_> = (0..5).collect();
+/// assert_eq!(skiplist[3], 3);
+/// skiplist[3] = 10;
+/// assert_eq!(skiplist[3], 10);
+///
+/// let result = std::panic::catch_unwind(|| skiplist[12]);
+/// let message = result.unwrap_err().downcast::<String>().unwrap();
+/// assert_eq!(*message, "index 12 out of bounds for skiplist of length 5");
+/// ```
+impl<T> ops::Index<usize> for SkipList<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        self.get(index)
+            .unwrap_or_else(|| index_out_of_bounds(index, self.len))
+    }
+}
+
+impl<T> ops::IndexMut<usize> for SkipList<T> {
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        let len = self.len;
+        self.get_mut(index)
+            .unwrap_or_else(|| index_out_of_bounds(index, len))
+    }
+}
+
+impl<T: PartialEq> PartialEq<[T]> for SkipList<T> {
+    #[inline]
+    fn eq(&self, other: &[T]) -> bool {
+        self.eq_slice(other)
+    }
+}
+
+/// 消耗 skiplist，按顺序取出所有元素，也可以从末尾反向取出。
+///
+/// # Examples
+///
+/// ```
+/// use skiplist::SkipList;
+///
+/// let skiplist: SkipList<_> = (1..6).map(|x| x.to_string()).collect();
+/// let mut iter = skiplist.into_iter();
+/// let mut order = Vec::new();
+/// while let Some(front) = iter.next() {
+///     order.push(front);
+///     if let Some(back) = iter.next_back() {
+///         order.push(back);
+///     }
+/// }
+/// assert_eq!(order, ["1", "5", "2", "4", "3"]);
+///
+/// let skiplist: SkipList<_> = (0..5).collect();
+/// assert!(skiplist.into_iter().rev().eq((0..5).rev()));
+/// ```
+impl<T> iter::IntoIterator for SkipList<T> {
+    type Item = T;
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> IntoIter<T> {
+        IntoIter {
+            head: self.head,
+            size: self.len,
+        }
+    }
+}
+
+impl<'a, T> iter::IntoIterator for &'a SkipList<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T> iter::IntoIterator for &'a mut SkipList<T> {
+    type Item = &'a mut T;
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T> iter::Extend<T> for SkipList<T> {
+    #[inline]
+    fn extend<I: iter::IntoIterator<Item = T>>(&mut self, iterable: I) {
+        self.append_iter(iterable);
+    }
+}
+
+impl<T> iter::FromIterator<T> for SkipList<T> {
+    #[inline]
+    fn from_iter<I>(iter: I) -> SkipList<T>
+    where
+        I: iter::IntoIterator<Item = T>,
+    {
+        let mut skiplist = SkipList::new();
+        skiplist.extend(iter);
+        skiplist
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SkipList;
+
+    #[test]
+    fn with_capacity_levels_for_small_capacities() {
+        for (capacity, levels) in [(0, 1), (1, 1), (2, 1), (3, 2)] {
+            assert_eq!(
+                SkipList::<i32>::with_capacity(capacity).head.links.len(),
+                levels
+            );
+        }
+    }
+}
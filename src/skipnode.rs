@@ -1,8 +1,4 @@
-use std::cmp::Ordering;
-use std::{
-    fmt, iter,
-    ptr::{self, NonNull},
-};
+use std::{cmp, collections::TryReserveError, fmt, iter, marker::PhantomData, mem, ptr::NonNull};
 
 /// 简写
 type Link<T> = Option<NonNull<SkipNode<T>>>;
@@ -17,11 +13,960 @@ type Link<T> = Option<NonNull<SkipNode<T>>>;
 /// 有一个对应的链路长度向量，其中包含当前节点和下一个节点之间的距离。如果没有下一个节点，则距离是当前节点和最后一个可到达节点之间的距离。
 ///
 /// 最后，每个节点都包含一个指向前一个节点的链接，以防需要向后解析列表。
-#[derive(Clone, Debug)]
+/// 第一个节点的前一个节点是头节点，它的链接为空：头节点随 SkipList 一起移动，指向它的裸指针会失效。
+#[derive(Debug)]
 pub struct SkipNode<V> {
-    pub item: Option<V>,
-    pub level: usize,
-    pub prev: Link<V>,
-    pub links: Vec<Link<V>>,
-    pub links_len: Vec<usize>,
-}
\ No newline at end of file
+    pub(crate) item: Option<V>,
+    pub(crate) level: usize,
+    pub(crate) prev: Link<V>,
+    pub(crate) links: Vec<Link<V>>,
+    pub(crate) links_len: Vec<usize>,
+}
+
+// SkipNode 的构造与访问
+impl<V> SkipNode<V> {
+    /// 创建一个拥有 `total_levels` 层链接的头节点。
+    pub fn head(total_levels: usize) -> Self {
+        SkipNode {
+            item: None,
+            level: total_levels - 1,
+            prev: None,
+            links: iter::repeat_n(None, total_levels).collect(),
+            links_len: iter::repeat_n(0, total_levels).collect(),
+        }
+    }
+
+    /// 创建一个位于 `level` 层的新节点。
+    pub fn new(item: V, level: usize) -> Self {
+        SkipNode {
+            item: Some(item),
+            level,
+            prev: None,
+            links: iter::repeat_n(None, level + 1).collect(),
+            links_len: iter::repeat_n(0, level + 1).collect(),
+        }
+    }
+
+    /// 取出节点中的值。
+    pub fn into_inner(mut self) -> Option<V> {
+        self.item.take()
+    }
+
+    /// 在调试构建中检查节点的层级与链接向量的长度是否一致。
+    fn debug_assert_tower(&self) {
+        debug_assert_eq!(
+            self.links.len(),
+            self.level + 1,
+            "Node links do not match its level!"
+        );
+        debug_assert_eq!(
+            self.links_len.len(),
+            self.links.len(),
+            "Node links_len do not match its links!"
+        );
+    }
+
+    /// 当前节点是否为头节点。
+    pub fn is_head(&self) -> bool {
+        self.item.is_none()
+    }
+
+    /// 第 0 层的下一个节点。
+    pub fn next_ref(&self) -> Option<&Self> {
+        // SAFETY: 第 0 层链接指向由当前节点拥有的有效节点
+        self.links[0].map(|next| unsafe { &*next.as_ptr() })
+    }
+
+    /// 第 0 层的上一个节点，头节点和第一个节点返回 `None`。
+    pub fn prev_ref(&self) -> Option<&Self> {
+        // SAFETY: `prev` 总是指向链表中位于当前节点之前的有效节点
+        self.prev.map(|prev| unsafe { &*prev.as_ptr() })
+    }
+
+    /// 从当前节点向后前进 `distance` 个节点，沿途尽可能使用高层链接。
+    ///
+    /// 如果链表中没有足够的节点，则返回 `None`。
+    pub fn advance(&self, distance: usize) -> Option<&Self> {
+        let mut node = self;
+        let mut distance_left = distance;
+        for level in (0..=self.level).rev() {
+            while let Some(next) = node.links[level] {
+                if node.links_len[level] > distance_left {
+                    break;
+                }
+                distance_left -= node.links_len[level];
+                // SAFETY: 链接指向链表中的有效节点
+                node = unsafe { &*next.as_ptr() };
+            }
+        }
+        if distance_left == 0 {
+            Some(node)
+        } else {
+            None
+        }
+    }
+
+    /// 从任意节点向后前进 `distance` 个节点。
+    ///
+    /// 与 `advance` 只能使用当前节点的塔不同，每一步都选择所在节点上不越过目标的最高链接，
+    /// 遇到更高的节点时会随之升高，因此从链表中间出发也只需 O(log n) 步。
+    pub fn seek(&self, distance: usize) -> Option<&Self> {
+        let mut node = self;
+        let mut distance_left = distance;
+        while distance_left > 0 {
+            let level = (0..=node.level).rev().find(|&level| {
+                node.links[level].is_some() && node.links_len[level] <= distance_left
+            })?;
+            distance_left -= node.links_len[level];
+            // SAFETY: 链接指向链表中的有效节点
+            node = unsafe { &*node.links[level]?.as_ptr() };
+        }
+        Some(node)
+    }
+
+    /// 从当前节点逐层下降，找到最后一个值满足 `pred` 的节点，返回该节点及其相对位置。
+    ///
+    /// `pred` 必须对链表的某个前缀成立、对其余部分不成立；如果没有节点满足，返回当前节点和 0。
+    pub fn find_last<F>(&self, mut pred: F) -> (&Self, usize)
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let mut node = self;
+        let mut pos = 0;
+        for level in (0..=self.level).rev() {
+            while let Some(next) = node.links[level] {
+                // SAFETY: 链接指向链表中的有效节点
+                let next = unsafe { &*next.as_ptr() };
+                if !next.item.as_ref().is_some_and(&mut pred) {
+                    break;
+                }
+                pos += node.links_len[level];
+                node = next;
+            }
+        }
+        (node, pos)
+    }
+
+    /// `advance` 的可变版本。
+    pub fn advance_mut(&mut self, distance: usize) -> Option<&mut Self> {
+        // SAFETY: 通过 `&mut self` 独占整个链表
+        self.advance_link(distance)
+            .map(|node| unsafe { &mut *node.as_ptr() })
+    }
+
+    /// 与 `advance_mut` 相同，但返回链表中保存的链接本身，而不是新创建的可变借用。
+    ///
+    /// `distance` 大于 0 时，之后经由其他链接修改节点不会使返回的指针失效，
+    /// 因此可以长期保存，例如作为光标的当前节点。
+    pub fn advance_link(&mut self, distance: usize) -> Option<NonNull<Self>> {
+        let top = self.level;
+        let mut node = NonNull::from(self);
+        let mut distance_left = distance;
+        for level in (0..=top).rev() {
+            loop {
+                // SAFETY: `node` 要么是 `self`，要么是从它出发可到达的节点
+                let current = unsafe { node.as_ref() };
+                match current.links[level] {
+                    Some(next) if current.links_len[level] <= distance_left => {
+                        distance_left -= current.links_len[level];
+                        node = next;
+                    }
+                    _ => break,
+                }
+            }
+        }
+        if distance_left == 0 {
+            Some(node)
+        } else {
+            None
+        }
+    }
+}
+
+// 头节点上的结构性操作。
+//
+// 位置以头节点为 0，第 i 个元素位于位置 `i + 1`。
+impl<V> SkipNode<V> {
+    /// 位于位置 `pred_pos` 的前驱 `pred` 作为 `prev` 链接时的值，头节点（位置 0）对应空链接。
+    fn prev_link(pred: NonNull<Self>, pred_pos: usize) -> Link<V> {
+        if pred_pos == 0 {
+            None
+        } else {
+            Some(pred)
+        }
+    }
+
+    /// 对每一层找到位置严格小于 `pos` 的最后一个节点，返回该节点及其位置。
+    fn predecessors(&mut self, pos: usize) -> Vec<(NonNull<Self>, usize)> {
+        let top = self.level;
+        let mut node = NonNull::from(self);
+        let mut node_pos = 0;
+        let mut preds = vec![(node, 0); top + 1];
+        for level in (0..=top).rev() {
+            loop {
+                // SAFETY: `node` 是从头节点出发可到达的有效节点
+                let current = unsafe { node.as_ref() };
+                match current.links[level] {
+                    Some(next) if node_pos + current.links_len[level] < pos => {
+                        node_pos += current.links_len[level];
+                        node = next;
+                    }
+                    _ => break,
+                }
+            }
+            preds[level] = (node, node_pos);
+        }
+        preds
+    }
+
+    /// 对每一层找到值满足 `pred` 的最后一个节点（或头节点），返回该节点及其位置。
+    ///
+    /// `pred` 必须对链表的某个前缀成立、对其余部分不成立。
+    fn predecessors_by<F>(&mut self, mut pred: F) -> Vec<(NonNull<Self>, usize)>
+    where
+        F: FnMut(&V) -> bool,
+    {
+        let top = self.level;
+        let mut node = NonNull::from(self);
+        let mut node_pos = 0;
+        let mut preds = vec![(node, 0); top + 1];
+        for level in (0..=top).rev() {
+            loop {
+                // SAFETY: `node` 是从头节点出发可到达的有效节点
+                let current = unsafe { node.as_ref() };
+                let Some(next) = current.links[level] else {
+                    break;
+                };
+                let next_node = unsafe { next.as_ref() };
+                if !next_node.item.as_ref().is_some_and(&mut pred) {
+                    break;
+                }
+                node_pos += current.links_len[level];
+                node = next;
+            }
+            preds[level] = (node, node_pos);
+        }
+        preds
+    }
+
+    /// 将 `new_node` 插入到索引 `index` 处，返回插入后的节点。
+    ///
+    /// 如果 `index` 大于链表长度，则原样返回 `new_node`。
+    pub fn insert_at(&mut self, new_node: Box<Self>, index: usize) -> Result<&mut Self, Box<Self>> {
+        assert!(self.is_head(), "Only the head may insert node!");
+        assert!(
+            self.level >= new_node.level,
+            "You may not insert nodes with level higher than the head!"
+        );
+        self.debug_assert_tower();
+        let preds = self.predecessors(index + 1);
+        if preds[0].1 != index {
+            return Err(new_node);
+        }
+        // SAFETY: `preds` 刚由 `predecessors` 求出，之后没有再通过 `self` 访问链表
+        Ok(unsafe { Self::link(&preds, new_node) })
+    }
+
+    /// 在排好序的链表中，把 `new_node` 插入到第一个不小于它的节点之前，返回插入的索引。
+    ///
+    /// 如果已经存在相等的值，则原样返回 `new_node`。查找和插入共用同一次下降。
+    pub(crate) fn insert_sorted_unique(&mut self, new_node: Box<Self>) -> Result<usize, Box<Self>>
+    where
+        V: Ord,
+    {
+        assert!(self.is_head(), "Only the head may insert node!");
+        assert!(
+            self.level >= new_node.level,
+            "You may not insert nodes with level higher than the head!"
+        );
+        let value = new_node
+            .item
+            .as_ref()
+            .expect("Only value nodes may be inserted!");
+        self.debug_assert_tower();
+        let preds = self.predecessors_by(|item| item < value);
+        // SAFETY: 第 0 层的前驱是链表中的有效节点
+        let next = unsafe { preds[0].0.as_ref() }.next_ref();
+        if next.and_then(|next| next.item.as_ref()) == Some(value) {
+            return Err(new_node);
+        }
+        let index = preds[0].1;
+        // SAFETY: 同 `insert_at`
+        unsafe { Self::link(&preds, new_node) };
+        Ok(index)
+    }
+
+    /// 把 `new_node` 链接到 `preds` 所描述的各层前驱之后，返回插入后的节点。
+    ///
+    /// 头节点只通过 `preds` 中的指针访问：在求出 `preds` 之后再使用 `&mut self`
+    /// 会重新借用头节点，使 `preds` 中指向头节点的指针失效。
+    ///
+    /// # Safety
+    ///
+    /// `preds` 必须是 `predecessors` 或 `predecessors_by` 对同一个头节点的返回值，
+    /// 并且在此之后没有通过其他引用访问过该链表。
+    unsafe fn link<'a>(preds: &[(NonNull<Self>, usize)], mut new_node: Box<Self>) -> &'a mut Self {
+        new_node.debug_assert_tower();
+        let pos = preds[0].1 + 1;
+        for (level, &(mut pred, pred_pos)) in preds.iter().enumerate() {
+            // SAFETY: 前驱节点均为链表中的有效节点，每次只持有其中一个的引用
+            let pred = unsafe { pred.as_mut() };
+            if level <= new_node.level {
+                new_node.links[level] = pred.links[level];
+                new_node.links_len[level] = pred_pos + pred.links_len[level] + 1 - pos;
+                pred.links_len[level] = pos - pred_pos;
+            } else {
+                pred.links_len[level] += 1;
+            }
+        }
+        new_node.prev = Self::prev_link(preds[0].0, preds[0].1);
+        let new_level = new_node.level;
+        let mut new_ptr = NonNull::from(Box::leak(new_node));
+        for (level, &(mut pred, _)) in preds[..=new_level].iter().enumerate() {
+            // SAFETY: 同上，第 0 层的前驱从此拥有新节点
+            unsafe { pred.as_mut() }.links[level] = Some(new_ptr);
+        }
+        // SAFETY: 新节点刚刚被链接进链表
+        let new_node = unsafe { new_ptr.as_mut() };
+        if let Some(mut next) = new_node.links[0] {
+            unsafe { next.as_mut() }.prev = Some(new_ptr);
+        }
+        new_node
+    }
+
+    /// 移除并返回索引 `index` 处的节点，返回的节点不再持有任何链接。
+    ///
+    /// 如果 `index` 越界，则返回 `None`。
+    pub fn remove_at(&mut self, index: usize) -> Option<Box<Self>> {
+        assert!(self.is_head(), "Only the head may remove node!");
+        self.debug_assert_tower();
+        let preds = self.predecessors(index + 1);
+        if preds[0].1 != index {
+            return None;
+        }
+        // SAFETY: 第 0 层的前驱拥有目标节点，这里把所有权转移出来
+        let target = unsafe { preds[0].0.as_ref() }.links[0]?;
+        let mut target = unsafe { Box::from_raw(target.as_ptr()) };
+        target.debug_assert_tower();
+        for (level, &(mut pred, _)) in preds.iter().enumerate() {
+            // SAFETY: 前驱节点均为链表中的有效节点
+            let pred = unsafe { pred.as_mut() };
+            if level <= target.level {
+                pred.links[level] = target.links[level].take();
+                pred.links_len[level] += target.links_len[level];
+                pred.links_len[level] -= 1;
+                target.links_len[level] = 0;
+            } else {
+                pred.links_len[level] -= 1;
+            }
+        }
+        let (prev, prev_pos) = preds[0];
+        // SAFETY: 前驱与后继节点仍在链表中
+        if let Some(mut next) = unsafe { prev.as_ref() }.links[0] {
+            unsafe { next.as_mut() }.prev = Self::prev_link(prev, prev_pos);
+        }
+        target.prev = None;
+        Some(target)
+    }
+
+    /// 把头节点增高到 `total_levels` 层，`len` 为链表长度；已经足够高时什么也不做。
+    ///
+    /// 新增的层上还没有任何节点，因此它们的链路长度为到最后一个节点的距离。
+    pub(crate) fn grow_head(&mut self, total_levels: usize, len: usize) {
+        assert!(self.is_head(), "Only the head may grow!");
+        if total_levels <= self.links.len() {
+            return;
+        }
+        self.links.resize(total_levels, None);
+        self.links_len.resize(total_levels, len);
+        self.level = total_levels - 1;
+    }
+
+    /// 与 `grow_head` 相同，但先预留链接向量的空间，分配失败时返回错误且头节点保持不变。
+    pub(crate) fn try_grow_head(
+        &mut self,
+        total_levels: usize,
+        len: usize,
+    ) -> Result<(), TryReserveError> {
+        let additional = total_levels.saturating_sub(self.links.len());
+        self.links.try_reserve_exact(additional)?;
+        self.links_len.try_reserve_exact(additional)?;
+        self.grow_head(total_levels, len);
+        Ok(())
+    }
+
+    /// 把头节点降低到 `total_levels` 层，更高的节点的塔被截断；已经足够矮时什么也不做。
+    ///
+    /// 较低各层的链接和链路长度与更高的层无关，因此截断后仍然正确。
+    pub(crate) fn shrink_head(&mut self, total_levels: usize) {
+        assert!(self.is_head(), "Only the head may shrink!");
+        assert!(total_levels > 0, "The head must keep at least one level!");
+        if total_levels >= self.links.len() {
+            return;
+        }
+        // 第 `total_levels` 层上的节点正好是所有需要截断的节点
+        let mut next = self.links[total_levels];
+        while let Some(mut ptr) = next {
+            // SAFETY: 链接指向链表中的有效节点，`&mut self` 保证了独占访问
+            let node = unsafe { ptr.as_mut() };
+            next = node.links[total_levels];
+            node.links.truncate(total_levels);
+            node.links_len.truncate(total_levels);
+            node.level = total_levels - 1;
+        }
+        self.links.truncate(total_levels);
+        self.links_len.truncate(total_levels);
+        self.level = total_levels - 1;
+    }
+
+    /// 返回每一层的最后一个节点及其位置，`len` 为链表长度。
+    ///
+    /// 结果可以交给 `push_tail` 在末尾连续追加节点。
+    pub(crate) fn tails(&mut self, len: usize) -> Vec<(NonNull<Self>, usize)> {
+        self.predecessors(len + 1)
+    }
+
+    /// 把 `new_node` 链接到 `tails` 所描述的链表末尾，并更新 `tails`。
+    ///
+    /// 每次追加只需 O(层数)，不需要从头节点重新下降。
+    /// `tails` 必须由 `tails` 方法得到，且期间链表没有被其他方式修改。
+    pub(crate) fn push_tail(tails: &mut [(NonNull<Self>, usize)], mut new_node: Box<Self>) {
+        assert!(
+            tails.len() > new_node.level,
+            "You may not insert nodes with level higher than the head!"
+        );
+        let pos = tails[0].1 + 1;
+        new_node.prev = Self::prev_link(tails[0].0, tails[0].1);
+        let new_level = new_node.level;
+        let new_ptr = NonNull::from(Box::leak(new_node));
+        for (level, (tail, tail_pos)) in tails.iter_mut().enumerate() {
+            // SAFETY: 每一层的尾节点都是链表中的有效节点
+            let tail_node = unsafe { tail.as_mut() };
+            if level <= new_level {
+                tail_node.links[level] = Some(new_ptr);
+                tail_node.links_len[level] = pos - *tail_pos;
+                *tail = new_ptr;
+                *tail_pos = pos;
+            } else {
+                tail_node.links_len[level] += 1;
+            }
+        }
+    }
+
+    /// 把 `other` 头节点之后的整条链拼接到当前链表末尾，`other` 变为空链表。
+    ///
+    /// `len` 和 `other_len` 分别为两个链表的长度，`other` 的层数不能超过当前头节点。
+    pub(crate) fn append(&mut self, len: usize, other: &mut Self, other_len: usize) {
+        assert!(
+            self.is_head() && other.is_head(),
+            "Only heads may be appended!"
+        );
+        assert!(
+            self.level >= other.level,
+            "You may not append a list that is higher than the head!"
+        );
+        let tails = self.tails(len);
+        for (level, &(mut tail, tail_pos)) in tails.iter().enumerate() {
+            // SAFETY: 每一层的尾节点都是链表中的有效节点
+            let tail = unsafe { tail.as_mut() };
+            if level <= other.level {
+                tail.links[level] = other.links[level].take();
+                tail.links_len[level] = len - tail_pos + other.links_len[level];
+                other.links_len[level] = 0;
+            } else {
+                tail.links_len[level] += other_len;
+            }
+        }
+        // SAFETY: `other` 的第一个节点现在由当前链表的尾节点拥有
+        let (last, last_pos) = tails[0];
+        if let Some(mut first) = unsafe { last.as_ref() }.links[0] {
+            unsafe { first.as_mut() }.prev = Self::prev_link(last, last_pos);
+        }
+    }
+
+    /// 把索引 `index` 及其之后的节点拆分到一个新的头节点中，新头节点与当前头节点层数相同。
+    ///
+    /// `index` 不能大于链表长度。
+    pub(crate) fn split_off(&mut self, index: usize) -> Box<Self> {
+        assert!(self.is_head(), "Only the head may split off nodes!");
+        let mut tail = Box::new(SkipNode::head(self.level + 1));
+        let preds = self.predecessors(index + 1);
+        for (level, &(mut pred, pred_pos)) in preds.iter().enumerate() {
+            // SAFETY: 前驱节点均为链表中的有效节点
+            let pred = unsafe { pred.as_mut() };
+            tail.links[level] = pred.links[level].take();
+            tail.links_len[level] = pred_pos + pred.links_len[level] - index;
+            pred.links_len[level] = index - pred_pos;
+        }
+        if let Some(mut first) = tail.links[0] {
+            // SAFETY: 第一个节点现在由新的头节点拥有
+            unsafe { first.as_mut() }.prev = None;
+        }
+        tail
+    }
+
+    /// 单次遍历整条链，只保留 `keep` 返回 `true` 的节点，并同时重建所有层的链接。
+    ///
+    /// `keep` 的第一个参数是上一个被保留的值，第二个参数是当前值。
+    /// 结束时 `len` 更新为保留的节点个数。即使 `keep` 发生恐慌，剩余节点也会被保留，链表保持有效。
+    pub(crate) fn retain<F>(&mut self, len: &mut usize, mut keep: F)
+    where
+        F: FnMut(Option<&V>, &mut V) -> bool,
+    {
+        let mut pass = self.retain_pass(len);
+        while let Some(mut ptr) = pass.next {
+            // SAFETY: `next` 指向尚未处理的节点，上一个被保留的节点与它不同
+            let current = unsafe { ptr.as_mut() };
+            let last = pass.tails[0];
+            let prev = if last.1 == 0 {
+                None
+            } else {
+                unsafe { last.0.as_ref() }.item.as_ref()
+            };
+            if keep(prev, current.item.as_mut().unwrap()) {
+                pass.keep_next();
+            } else {
+                pass.remove_next();
+            }
+        }
+    }
+
+    /// 开始一次逐个决定保留或移除节点的遍历，`len` 为链表长度。
+    ///
+    /// 遍历期间 `len` 被置为 0，`RetainPass` 析构时才写回剩余的长度；
+    /// 因此即使 `RetainPass` 被泄漏，链表也只会表现为空而不会失效。
+    pub(crate) fn retain_pass<'a>(&'a mut self, len: &'a mut usize) -> RetainPass<'a, V> {
+        assert!(self.is_head(), "Only the head may retain nodes!");
+        let top = self.level;
+        let next = self.links[0];
+        self.links.iter_mut().for_each(|link| *link = None);
+        self.links_len.iter_mut().for_each(|len| *len = 0);
+        self.links[0] = next;
+        let head = NonNull::from(self);
+        RetainPass {
+            tails: vec![(head, 0); top + 1],
+            next,
+            len: mem::replace(len, 0),
+            out: len,
+        }
+    }
+
+    /// 以第 0 层链为准重建头节点之后的整个结构，并把 `len` 改为实际的节点个数。
+    ///
+    /// 高于头节点的塔被截断，链接向量的长度与层级不符的会被修正，
+    /// 所有高层链接、链路长度和 `prev` 都会重新计算。
+    pub(crate) fn repair(&mut self, len: &mut usize) {
+        assert!(self.is_head(), "Only the head may be repaired!");
+        let top = self.level;
+        self.links.resize(top + 1, None);
+        self.links_len.resize(top + 1, 0);
+        let mut next = self.links[0];
+        while let Some(mut ptr) = next {
+            // SAFETY: 第 0 层链上的节点都是有效节点，`&mut self` 保证了独占访问
+            let node = unsafe { ptr.as_mut() };
+            node.level = cmp::min(node.level, top);
+            node.links.resize(node.level + 1, None);
+            node.links_len.resize(node.level + 1, 0);
+            next = node.links[0];
+        }
+        // 保留所有节点的遍历会重新链接每一层，并写回实际的长度
+        drop(self.retain_pass(len));
+    }
+}
+
+/// 逐个保留或移除节点的遍历状态，由 [`SkipNode::retain_pass`] 创建。
+///
+/// `tails` 为每一层最后一个被保留的节点及其新位置，`next` 为尚未处理的链，
+/// 第 0 层始终从最后一个被保留的节点连到 `next`。
+/// 析构时保留所有剩余节点，把各层末尾的链接收尾，并写回长度。
+pub(crate) struct RetainPass<'a, V> {
+    tails: Vec<(NonNull<SkipNode<V>>, usize)>,
+    next: Link<V>,
+    len: usize,
+    out: &'a mut usize,
+}
+
+impl<V> RetainPass<'_, V> {
+    /// 下一个尚未处理的值。
+    pub(crate) fn peek_next(&mut self) -> Option<&mut V> {
+        // SAFETY: `next` 指向尚未处理的有效节点，且只能通过 `&mut self` 访问
+        self.next
+            .map(|mut ptr| unsafe { ptr.as_mut() })
+            .and_then(|node| node.item.as_mut())
+    }
+
+    /// 尚未处理的节点个数。
+    pub(crate) fn remaining(&self) -> usize {
+        self.len - self.tails[0].1
+    }
+
+    /// 保留下一个节点，把它接到每一层的尾部。
+    pub(crate) fn keep_next(&mut self) {
+        let Some(mut ptr) = self.next else {
+            return;
+        };
+        // SAFETY: `next` 指向尚未处理的有效节点，各层尾节点都已被重新链接
+        let node = unsafe { ptr.as_mut() };
+        self.next = node.links[0];
+        let pos = self.tails[0].1 + 1;
+        node.prev = SkipNode::prev_link(self.tails[0].0, self.tails[0].1);
+        for (level, (tail, tail_pos)) in self.tails[..=node.level].iter_mut().enumerate() {
+            let tail_node = unsafe { tail.as_mut() };
+            tail_node.links[level] = Some(ptr);
+            tail_node.links_len[level] = pos - *tail_pos;
+            *tail = ptr;
+            *tail_pos = pos;
+        }
+    }
+
+    /// 移除下一个节点并返回它的值。
+    pub(crate) fn remove_next(&mut self) -> Option<V> {
+        let ptr = self.next?;
+        // SAFETY: 未处理的链归 `next` 所有，第 0 层的尾节点随即改为指向它的后继
+        let mut node = unsafe { Box::from_raw(ptr.as_ptr()) };
+        self.next = node.links[0].take();
+        unsafe { self.tails[0].0.as_mut() }.links[0] = self.next;
+        self.len -= 1;
+        node.into_inner()
+    }
+}
+
+impl<V> Drop for RetainPass<'_, V> {
+    fn drop(&mut self) {
+        while self.next.is_some() {
+            self.keep_next();
+        }
+        let len = self.tails[0].1;
+        for (level, (tail, tail_pos)) in self.tails.iter_mut().enumerate() {
+            // SAFETY: 尾节点均为链表中的有效节点
+            let tail = unsafe { tail.as_mut() };
+            tail.links[level] = None;
+            tail.links_len[level] = len - *tail_pos;
+        }
+        // 所有节点都已处理，被保留的节点个数就是新的长度
+        *self.out = len;
+    }
+}
+
+impl<V> Drop for SkipNode<V> {
+    // 逐个释放后继节点，避免长链表上的递归析构
+    fn drop(&mut self) {
+        let mut next = self.links.first_mut().and_then(Option::take);
+        while let Some(node) = next {
+            // SAFETY: 第 0 层链接拥有它所指向的节点
+            let mut node = unsafe { Box::from_raw(node.as_ptr()) };
+            next = node.links[0].take();
+        }
+    }
+}
+
+/// 由 [`SkipList::into_parts`](crate::SkipList::into_parts) 交出的整条节点链，包括头节点。
+///
+/// 它的内容不对外公开，只能原样交还给 [`SkipList::from_parts`](crate::SkipList::from_parts)；
+/// 被丢弃时释放链上的所有节点。
+pub struct NodeChain<T>(pub(crate) Box<SkipNode<T>>);
+
+/// 指向 SkipList 中某个节点的不透明指针，由 [`SkipList::head_ptr`](crate::SkipList::head_ptr) 返回。
+///
+/// 它只记录节点的地址，本身不借用 skiplist，因此解引用它的方法都是 `unsafe` 的。
+pub struct NodePtr<T>(pub(crate) NonNull<SkipNode<T>>);
+
+impl<T> NodePtr<T> {
+    /// 第 0 层的下一个节点，当前节点是最后一个节点时返回 `None`。
+    ///
+    /// # Safety
+    ///
+    /// 得到该指针之后，产生它的 skiplist 必须没有被修改、移动或释放。
+    pub unsafe fn next(self) -> Option<NodePtr<T>> {
+        // SAFETY: 由调用者保证节点仍然有效
+        unsafe { self.0.as_ref() }.links[0].map(NodePtr)
+    }
+
+    /// 节点中的元素，头节点返回 `None`。
+    ///
+    /// # Safety
+    ///
+    /// 与 [`next`](NodePtr::next) 相同，并且在返回的引用不再使用之前，
+    /// skiplist 同样不能被修改、移动或释放。
+    pub unsafe fn item<'a>(self) -> Option<&'a T> {
+        // SAFETY: 由调用者保证节点在 `'a` 期间有效且不被修改
+        unsafe { &*self.0.as_ptr() }.item.as_ref()
+    }
+}
+
+impl<T> Clone for NodePtr<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for NodePtr<T> {}
+
+impl<T> PartialEq for NodePtr<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<T> Eq for NodePtr<T> {}
+
+impl<T> fmt::Debug for NodePtr<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("NodePtr").field(&self.0).finish()
+    }
+}
+
+/// SkipList 的不可变迭代器。
+pub struct Iter<'a, T> {
+    pub(crate) first: Option<&'a SkipNode<T>>,
+    pub(crate) last: Option<&'a SkipNode<T>>,
+    pub(crate) size: usize,
+}
+
+impl<'a, T> Iter<'a, T> {
+    /// 从头节点之后开始迭代 `size` 个节点。
+    pub(crate) fn from_head(head: &'a SkipNode<T>, size: usize) -> Self {
+        Iter::from_range(head, 0, size)
+    }
+
+    /// 迭代索引位于 `[start, end)` 内的节点，调用方保证 `start <= end <= len`。
+    pub(crate) fn from_range(head: &'a SkipNode<T>, start: usize, end: usize) -> Self {
+        if start == end {
+            return Iter {
+                first: None,
+                last: None,
+                size: 0,
+            };
+        }
+        Iter {
+            first: head.advance(start + 1),
+            last: head.advance(end),
+            size: end - start,
+        }
+    }
+}
+
+impl<T> Clone for Iter<'_, T> {
+    fn clone(&self) -> Self {
+        Iter {
+            first: self.first,
+            last: self.last,
+            size: self.size,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        let node = self.first?;
+        self.size -= 1;
+        self.first = node.next_ref();
+        node.item.as_ref()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+
+    fn count(self) -> usize {
+        self.size
+    }
+
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.size {
+            self.first = None;
+            self.size = 0;
+            return None;
+        }
+        self.first = self.first?.seek(n);
+        self.size -= n;
+        self.next()
+    }
+}
+
+impl<T> DoubleEndedIterator for Iter<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        let node = self.last?;
+        self.size -= 1;
+        self.last = node.prev_ref();
+        node.item.as_ref()
+    }
+}
+
+impl<T> ExactSizeIterator for Iter<'_, T> {}
+
+/// SkipList 的可变迭代器。
+pub struct IterMut<'a, T> {
+    pub(crate) first: Option<NonNull<SkipNode<T>>>,
+    pub(crate) last: Option<NonNull<SkipNode<T>>>,
+    pub(crate) size: usize,
+    pub(crate) _lifetime: PhantomData<&'a mut T>,
+}
+
+impl<'a, T> IterMut<'a, T> {
+    /// 从头节点之后开始迭代 `size` 个节点。
+    pub(crate) fn from_head(head: &'a mut SkipNode<T>, size: usize) -> Self {
+        IterMut::from_range(head, 0, size)
+    }
+
+    /// 迭代索引位于 `[start, end)` 内的节点，调用方保证 `start <= end <= len`。
+    pub(crate) fn from_range(head: &'a mut SkipNode<T>, start: usize, end: usize) -> Self {
+        if start == end {
+            return IterMut {
+                first: None,
+                last: None,
+                size: 0,
+                _lifetime: PhantomData,
+            };
+        }
+        // 先定位末尾：到达 `start` 的下降路径只经过更靠前的节点，不会使 `last` 失效
+        let last = head.advance_mut(end).map(NonNull::from);
+        let first = if end - start == 1 {
+            last
+        } else {
+            head.advance_mut(start + 1).map(NonNull::from)
+        };
+        IterMut {
+            first,
+            last,
+            size: end - start,
+            _lifetime: PhantomData,
+        }
+    }
+}
+
+impl<'a, T> Iterator for IterMut<'a, T> {
+    type Item = &'a mut T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        // SAFETY: `size` 保证两端的游标不会越过对方，每个节点只会被访问一次，
+        // 因此返回的可变引用互不重叠
+        let node = unsafe { &mut *self.first?.as_ptr() };
+        self.size -= 1;
+        self.first = node.links[0];
+        node.item.as_mut()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+
+    fn count(self) -> usize {
+        self.size
+    }
+}
+
+impl<T> DoubleEndedIterator for IterMut<'_, T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        // SAFETY: 同 `next`
+        let node = unsafe { &mut *self.last?.as_ptr() };
+        self.size -= 1;
+        self.last = node.prev;
+        node.item.as_mut()
+    }
+}
+
+impl<T> ExactSizeIterator for IterMut<'_, T> {}
+
+/// SkipList 的所有权迭代器。
+pub struct IntoIter<T> {
+    pub(crate) head: Box<SkipNode<T>>,
+    pub(crate) size: usize,
+}
+
+impl<T> Iterator for IntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        self.size -= 1;
+        self.head.remove_at(0).and_then(|node| node.into_inner())
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.size, Some(self.size))
+    }
+
+    fn count(self) -> usize {
+        self.size
+    }
+}
+
+impl<T> DoubleEndedIterator for IntoIter<T> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.size == 0 {
+            return None;
+        }
+        // 两端都把节点从链中移除，剩余的链表长度始终等于 `size`，不会重复释放
+        self.size -= 1;
+        self.head
+            .remove_at(self.size)
+            .and_then(|node| node.into_inner())
+    }
+}
+
+impl<T> ExactSizeIterator for IntoIter<T> {}
+
+#[cfg(test)]
+mod tests {
+    use super::{NodeChain, SkipNode};
+    use crate::SkipList;
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Node links_len do not match its links!")]
+    fn insert_at_checks_new_tower() {
+        let mut head = SkipNode::head(4);
+        let mut node = SkipNode::new(0, 2);
+        node.links_len.pop();
+        let _ = head.insert_at(Box::new(node), 0);
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "Node links do not match its level!")]
+    fn remove_at_checks_head_tower() {
+        let mut head = SkipNode::head(4);
+        head.insert_at(Box::new(SkipNode::new(0, 0)), 0).unwrap();
+        head.level += 1;
+        head.remove_at(0);
+    }
+
+    #[test]
+    fn repair_rebuilds_corrupted_spans() {
+        let skiplist: SkipList<_> = (0..100).collect();
+        let (NodeChain(mut head), len, _) = skiplist.into_parts();
+        // 破坏所有高层跨度和长度
+        for span in &mut head.links_len[1..] {
+            *span += 7;
+        }
+        let mut len = len + 3;
+
+        head.repair(&mut len);
+        assert_eq!(len, 100);
+        for index in 0..100 {
+            assert_eq!(head.advance(index + 1).unwrap().item, Some(index));
+        }
+        assert!(head.advance(101).is_none());
+    }
+}